@@ -0,0 +1,148 @@
+// Solve time, labels created and peak memory of `find_optimal_path` on generated tracks, and
+// its timing against `find_optimal_path_bidirectional` on single-lap circuits.
+// Run with `cargo bench --bench solver`; the label and memory table is printed before timing.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use f1_track::bidirectional::find_optimal_path_bidirectional;
+use f1_track::generator::{generate, PitPlacement, Shape, TrackSpec};
+use f1_track::graph_rcspp::{find_optimal_path, Graph, RaceParams, Resolution, SolveOptions};
+use f1_track::input_parser::{CarConfig, RaceData};
+use f1_track::units::Fixed;
+
+// Counts live heap bytes and the high-water mark since the last reset
+struct CountingAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+struct Scenario {
+    name: &'static str,
+    spec: TrackSpec,
+    laps: u32,
+}
+
+fn scenarios() -> Vec<Scenario> {
+    let car = |fuel: i64| vec![CarConfig { fuel_capacity: Fixed::from_int(fuel), tyre_cost: Fixed::from_int(2) }];
+    let spec = |nodes, shape, shortcuts, pits, pit_placement, fuel| TrackSpec {
+        nodes,
+        shape,
+        shortcuts,
+        pits,
+        pit_placement,
+        cars: car(fuel),
+        seed: 42,
+        ..TrackSpec::default()
+    };
+    vec![
+        Scenario { name: "ring", spec: spec(50, Shape::Ring, 0, 5, PitPlacement::Spaced, 120), laps: 1 },
+        Scenario { name: "ring_3_laps", spec: spec(20, Shape::Ring, 0, 3, PitPlacement::Spaced, 120), laps: 3 },
+        Scenario { name: "grid", spec: spec(49, Shape::Grid, 0, 5, PitPlacement::Random, 120), laps: 1 },
+        Scenario { name: "circuit", spec: spec(50, Shape::Circuit, 5, 5, PitPlacement::Spaced, 120), laps: 1 },
+        Scenario { name: "circuit_pit_lanes", spec: spec(50, Shape::Circuit, 5, 4, PitPlacement::Lane, 120), laps: 1 },
+        Scenario { name: "circuit_dense_pits", spec: spec(50, Shape::Circuit, 5, 20, PitPlacement::Random, 120), laps: 1 },
+        Scenario { name: "circuit_big_tank", spec: spec(50, Shape::Circuit, 5, 5, PitPlacement::Spaced, 400), laps: 1 },
+        Scenario { name: "circuit_200", spec: spec(200, Shape::Circuit, 20, 25, PitPlacement::Spaced, 120), laps: 1 },
+    ]
+}
+
+fn params(race_data: &RaceData, laps: u32) -> RaceParams {
+    RaceParams {
+        start_node: 0,
+        end_node: race_data.n - 1,
+        total_laps: laps,
+        pit_stop_penalty: Fixed::from_int(60),
+        tyre_wear_distance: Fixed::from_int(100),
+        resolution: Resolution::EXACT,
+    }
+}
+
+fn print_metrics(scenarios: &[Scenario]) {
+    println!("{:<20} {:>6} {:>6} {:>10} {:>10} {:>12} {:>10}", "scenario", "nodes", "edges", "labels", "peak heap", "peak memory", "race time");
+    for scenario in scenarios {
+        let race_data = generate(&scenario.spec).unwrap();
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let params = params(&race_data, scenario.laps);
+        let baseline = LIVE.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        let outcome = find_optimal_path(&race_data, &graph, &race_data.cars[0], &params, &SolveOptions::default());
+        let peak = PEAK.load(Ordering::Relaxed) - baseline;
+        println!("{:<20} {:>6} {:>6} {:>10} {:>10} {:>10}KB {:>10}",
+            scenario.name, race_data.n, race_data.m, outcome.stats.labels_created, outcome.stats.peak_heap, peak / 1024,
+            outcome.best.map(|p| p.total_time.to_string()).unwrap_or_else(|| "-".to_string()));
+    }
+}
+
+fn bench_solver(c: &mut Criterion) {
+    let scenarios = scenarios();
+    print_metrics(&scenarios);
+
+    let mut group = c.benchmark_group("find_optimal_path");
+    for scenario in &scenarios {
+        let race_data = generate(&scenario.spec).unwrap();
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let params = params(&race_data, scenario.laps);
+        group.bench_with_input(BenchmarkId::from_parameter(scenario.name), &race_data, |b, race_data| {
+            b.iter(|| find_optimal_path(race_data, &graph, &race_data.cars[0], &params, &SolveOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+// A circuit with a few corners to cut and a pit node every seven or so nodes
+fn bidirectional_spec(n: usize) -> TrackSpec {
+    TrackSpec {
+        nodes: n,
+        shape: Shape::Circuit,
+        shortcuts: n / 5,
+        pits: n / 7,
+        pit_placement: PitPlacement::Spaced,
+        cars: vec![CarConfig { fuel_capacity: Fixed::from_int(120), tyre_cost: Fixed::from_int(2) }],
+        seed: 0x9E37_79B9_7F4A_7C15 ^ n as u64,
+        ..TrackSpec::default()
+    }
+}
+
+fn bench_bidirectional(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bidirectional");
+    for n in [50, 100, 200, 400] {
+        let race_data = generate(&bidirectional_spec(n)).unwrap();
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let params = params(&race_data, 1);
+        let car = &race_data.cars[0];
+        let options = SolveOptions::default();
+        let uni = find_optimal_path(&race_data, &graph, car, &params, &options).best.map(|p| p.total_time);
+        let bi = find_optimal_path_bidirectional(&race_data, &graph, car, &params, &options).best.map(|p| p.total_time);
+        assert_eq!(uni, bi, "solvers disagree on {} nodes", n);
+
+        group.bench_with_input(BenchmarkId::new("unidirectional", n), &race_data, |b, race_data| {
+            b.iter(|| find_optimal_path(race_data, &graph, car, &params, &options))
+        });
+        group.bench_with_input(BenchmarkId::new("bidirectional", n), &race_data, |b, race_data| {
+            b.iter(|| find_optimal_path_bidirectional(race_data, &graph, car, &params, &options))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_solver, bench_bidirectional);
+criterion_main!(benches);
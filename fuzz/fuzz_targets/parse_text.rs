@@ -0,0 +1,18 @@
+#![no_main]
+
+use f1_track::graph_rcspp::Graph;
+use f1_track::{input_parser, visual};
+use libfuzzer_sys::fuzz_target;
+
+// The text parser must either reject the input or return a track that passes validation,
+// and nothing downstream of an accepted track may panic on it.
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else { return };
+    let Ok(race_data) = input_parser::parse_str(text) else { return };
+    if let Err(e) = input_parser::validate(&race_data) {
+        panic!("parser accepted a track that fails validation: {}", e);
+    }
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    assert_eq!(graph.adj.len(), race_data.n);
+    visual::print_graph_visualization(&race_data, 0, race_data.n.saturating_sub(1));
+});
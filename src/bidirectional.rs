@@ -0,0 +1,563 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
+use crate::graph_rcspp::{self, find_optimal_path, Graph, PathInfo, RaceParams, SolveError, SolveOptions, SolveOutcome, SolveStats, SolveStatus, State};
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+
+// Bidirectional label-setting search for single-lap, point-to-point races.
+//
+// The forward half is the usual search from the start, but only labels faster than a
+// split time `h` are extended. The backward half grows suffixes from the finish over the
+// reversed graph, extending only suffixes whose cheapest possible time is below `h`.
+// Every forward state is then joined with every suffix at the same node. Any strategy
+// faster than `2h` is covered by some join, so `h` is doubled until the best join beats `2h`.
+//
+// Races with several laps, a start equal to the finish or a coarse resolution fall back to
+// `find_optimal_path`. Budgets are checked before every expansion in either half; when one
+// runs out, the best join found so far is returned as `Feasible`. Moves whose time overflows
+// are dropped and counted in `time_overflows`, as in `find_optimal_path`.
+pub fn find_optimal_path_bidirectional(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> SolveOutcome {
+    if params.total_laps != 1 || params.start_node == params.end_node || !params.resolution.is_exact() {
+        return find_optimal_path(race_data, graph, car_config, params, options);
+    }
+
+    let started = Instant::now();
+    let reversed = graph.reversed();
+    let mut stats = SolveStats::default();
+    let Some(shortest) = shortest_distance(graph, params, &mut stats) else {
+        stats.elapsed = started.elapsed();
+        return SolveOutcome { status: SolveStatus::Infeasible, best: None, lower_bound: None, stats };
+    };
+
+    let mut h = shortest.max(Fixed::EPSILON);
+    // No strategy is faster than this; it rises to `h` whenever a round ends without a finish
+    let mut lower_bound = shortest;
+    // Best join of the previous round, kept in case a budget cuts the next round short
+    let mut incumbent: Option<Candidate> = None;
+    let budget = Budget { options, started };
+    loop {
+        let forward = forward_labels(race_data, graph, car_config, params, h, &budget, &mut stats);
+        let backward = backward_labels(race_data, &reversed, car_config, params, h, &budget, &mut stats);
+        let best = join(race_data, car_config, params, &forward.states, &backward.suffixes, &mut stats);
+
+        if forward.interrupted || backward.interrupted {
+            let best = match (best, incumbent) {
+                (Some(candidate), Some(previous)) if previous.total_time < candidate.total_time => Some(previous),
+                (best, previous) => best.or(previous),
+            };
+            let best = best.map(|candidate| candidate.into_path_info(race_data, graph, car_config, params));
+            stats.elapsed = started.elapsed();
+            let (status, lower_bound) = match &best {
+                Some(path) => (SolveStatus::Feasible, Some(lower_bound.min(path.total_time))),
+                None => (SolveStatus::BudgetExceeded, Some(lower_bound)),
+            };
+            return SolveOutcome { status, best, lower_bound, stats };
+        }
+
+        let finished = h == Fixed::MAX
+            || match &best {
+                // A split time too large to double covers every representable time
+                Some(candidate) => h.checked_add(h).is_none_or(|limit| candidate.total_time < limit),
+                // Nothing was cut off, so the whole forward space was explored
+                None => !forward.truncated,
+            };
+        if finished {
+            let best = best.map(|candidate| candidate.into_path_info(race_data, graph, car_config, params));
+            stats.elapsed = started.elapsed();
+            let status = if best.is_some() { SolveStatus::Optimal } else { SolveStatus::Infeasible };
+            let lower_bound = best.as_ref().map(|p| p.total_time);
+            return SolveOutcome { status, best, lower_bound, stats };
+        }
+        h = h.checked_add(h).unwrap_or(Fixed::MAX);
+        lower_bound = h;
+        incumbent = best;
+    }
+}
+
+// The caller's budgets, shared by both halves across every round
+struct Budget<'a> {
+    options: &'a SolveOptions,
+    started: Instant,
+}
+
+impl Budget<'_> {
+    // Same checks as `find_optimal_path`, with `queued` labels left waiting
+    fn exceeded(&self, stats: &SolveStats, queued: usize) -> bool {
+        self.options.timeout.is_some_and(|t| self.started.elapsed() >= t)
+            || self.options.max_labels.is_some_and(|max| stats.labels_created > max)
+            || self.options.max_heap.is_some_and(|max| queued + 1 > max)
+    }
+}
+
+// Same as `graph_rcspp::solve`, but searching with `find_optimal_path_bidirectional`
+pub fn solve_bidirectional(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> Result<SolveOutcome, SolveError> {
+    graph_rcspp::checked_outcome(race_data, params, || {
+        find_optimal_path_bidirectional(race_data, graph, car_config, params, options)
+    })
+}
+
+// Plain shortest distance from start to finish, which also bounds the optimal race time from below
+fn shortest_distance(graph: &Graph, params: &RaceParams, stats: &mut SolveStats) -> Option<Fixed> {
+    let mut dist: HashMap<usize, Fixed> = HashMap::from([(params.start_node, Fixed::ZERO)]);
+    let mut heap = BinaryHeap::from([(std::cmp::Reverse(Fixed::ZERO), params.start_node)]);
+    while let Some((std::cmp::Reverse(d), u)) = heap.pop() {
+        if u == params.end_node {
+            return Some(d);
+        }
+        if dist.get(&u).is_some_and(|&best| d > best) {
+            continue;
+        }
+        for &(v, w) in &graph.adj[&u] {
+            if v == params.start_node {
+                continue;
+            }
+            let Some(next) = d.checked_add(w) else {
+                stats.time_overflows += 1;
+                continue;
+            };
+            if dist.get(&v).is_none_or(|&best| next < best) {
+                dist.insert(v, next);
+                heap.push((std::cmp::Reverse(next), v));
+            }
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone)]
+struct ForwardLabel {
+    time: Fixed,
+    path: Vec<usize>,
+    pit_stops: Vec<usize>,
+}
+
+struct ForwardSearch {
+    states: HashMap<State, ForwardLabel>,
+    // Some label reached the split time and was not extended
+    truncated: bool,
+    // A budget stopped the search before the split time was reached
+    interrupted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Queued {
+    time: Fixed,
+    state: State,
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.time.cmp(&self.time)
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn tyre_penalty(tyre: Fixed, params: &RaceParams, car_config: &CarConfig) -> Fixed {
+    graph_rcspp::tyre_penalty(tyre, params.tyre_wear_distance, car_config.tyre_cost)
+}
+
+// Best label per state, extending only labels faster than `h`
+fn forward_labels(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    h: Fixed,
+    budget: &Budget,
+    stats: &mut SolveStats,
+) -> ForwardSearch {
+    let initial = State { lap: 0, current_node: params.start_node, current_fuel: car_config.fuel_capacity, tyre_distance: Fixed::ZERO };
+    let mut states = HashMap::from([(initial.clone(), ForwardLabel { time: Fixed::ZERO, path: vec![params.start_node], pit_stops: Vec::new() })]);
+    let mut heap = BinaryHeap::from([Queued { time: Fixed::ZERO, state: initial }]);
+    let mut truncated = false;
+    let mut interrupted = false;
+    stats.labels_created += 1;
+
+    while let Some(Queued { time, state }) = heap.pop() {
+        if states[&state].time < time {
+            continue;
+        }
+        if time >= h {
+            truncated = true;
+            continue;
+        }
+        // Reaching the finish ends a single-lap race
+        if state.current_node == params.end_node {
+            continue;
+        }
+        if budget.exceeded(stats, heap.len()) {
+            interrupted = true;
+            break;
+        }
+        stats.labels_expanded += 1;
+        let label = states[&state].clone();
+        let is_pit_node = race_data.pit_nodes.contains(&state.current_node);
+        for &(next, distance) in &graph.adj[&state.current_node] {
+            // Entering the start again would begin a second lap
+            if next == params.start_node {
+                continue;
+            }
+            let mut moves = Vec::with_capacity(2);
+            if state.current_fuel >= distance {
+                moves.push((false, state.current_fuel, state.tyre_distance, time));
+            }
+            if is_pit_node && car_config.fuel_capacity >= distance {
+                match time.checked_add(params.pit_stop_penalty) {
+                    Some(departure_time) => moves.push((true, car_config.fuel_capacity, Fixed::ZERO, departure_time)),
+                    None => stats.time_overflows += 1,
+                }
+            }
+            for (pit, fuel, tyre, departure_time) in moves {
+                let tyre_after = tyre + distance;
+                let new_time = departure_time
+                    .checked_add(distance)
+                    .and_then(|t| t.checked_add(tyre_penalty(tyre_after, params, car_config)));
+                let Some(new_time) = new_time else {
+                    stats.time_overflows += 1;
+                    continue;
+                };
+                let new_state = State { lap: 0, current_node: next, current_fuel: fuel - distance, tyre_distance: tyre_after };
+                if states.get(&new_state).is_some_and(|existing| existing.time <= new_time) {
+                    continue;
+                }
+                let mut path = label.path.clone();
+                path.push(next);
+                let mut pit_stops = label.pit_stops.clone();
+                if pit {
+                    pit_stops.push(state.current_node);
+                }
+                states.insert(new_state.clone(), ForwardLabel { time: new_time, path, pit_stops });
+                heap.push(Queued { time: new_time, state: new_state });
+                stats.labels_created += 1;
+                stats.peak_heap = stats.peak_heap.max(heap.len());
+            }
+        }
+    }
+    ForwardSearch { states, truncated, interrupted }
+}
+
+// A suffix of the race from `nodes[0]` to the finish.
+//
+// With an exact resolution the car always has `tyre = capacity - fuel`, so the cost of the
+// stretch driven before the suffix's first pit stop depends only on the arrival fuel.
+#[derive(Debug, Clone)]
+struct Suffix {
+    nodes: Vec<usize>,
+    // Nodes (in order) where the suffix pits; may include `nodes[0]`
+    pit_stops: Vec<usize>,
+    // The suffix pits at `nodes[0]`, so its cost does not depend on the arrival state
+    pit_at_head: bool,
+    // Distance driven before the first pit stop, i.e. the fuel needed on arrival
+    open_distance: Fixed,
+    // Distance from `nodes[0]` to the end of each edge before the first pit stop
+    cumulative: Vec<Fixed>,
+    // Time of everything after the first pit stop, including its penalty
+    tail: Fixed,
+}
+
+impl Suffix {
+    // Suffix time for a car arriving with tyre wear `tyre` (and enough fuel), or `None` if it overflows
+    fn cost(&self, tyre: Fixed, params: &RaceParams, car_config: &CarConfig) -> Option<Fixed> {
+        if self.pit_at_head {
+            return Some(self.tail);
+        }
+        self.cumulative
+            .iter()
+            .try_fold(self.open_distance, |total, &c| total.checked_add(tyre_penalty(tyre + c, params, car_config)))
+            .and_then(|total| total.checked_add(self.tail))
+    }
+
+    // Cheapest this suffix can ever be: arriving on fresh tyres
+    fn key(&self, params: &RaceParams, car_config: &CarConfig) -> Option<Fixed> {
+        self.cost(Fixed::ZERO, params, car_config)
+    }
+
+    // At least as cheap for every arrival state `other` can serve
+    fn dominates(&self, other: &Suffix, params: &RaceParams, car_config: &CarConfig) -> bool {
+        if self.pit_at_head {
+            return other.pit_at_head && self.tail <= other.tail;
+        }
+        if other.pit_at_head || self.open_distance > other.open_distance {
+            return false;
+        }
+        // Both costs are piecewise linear in arrival tyre wear, so compare at every breakpoint
+        let max_tyre = car_config.fuel_capacity - other.open_distance;
+        let mut points = vec![Fixed::ZERO, max_tyre];
+        for &c in self.cumulative.iter().chain(&other.cumulative) {
+            let breakpoint = params.tyre_wear_distance - c;
+            if breakpoint > Fixed::ZERO && breakpoint < max_tyre {
+                points.push(breakpoint);
+            }
+        }
+        // An overflowing cost can never be used, so anything beats it
+        points.into_iter().all(|tyre| match (self.cost(tyre, params, car_config), other.cost(tyre, params, car_config)) {
+            (Some(mine), Some(theirs)) => mine <= theirs,
+            (_, None) => true,
+            (None, Some(_)) => false,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct QueuedSuffix {
+    key: Fixed,
+    node: usize,
+    id: usize,
+}
+
+impl PartialEq for QueuedSuffix {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.id == other.id
+    }
+}
+
+impl Eq for QueuedSuffix {}
+
+impl Ord for QueuedSuffix {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key).then(other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for QueuedSuffix {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Suffixes found so far, with the non-dominated ones indexed by their first node
+struct SuffixPool<'a> {
+    params: &'a RaceParams,
+    car_config: &'a CarConfig,
+    suffixes: Vec<Suffix>,
+    alive: Vec<bool>,
+    at_node: HashMap<usize, Vec<usize>>,
+    heap: BinaryHeap<QueuedSuffix>,
+}
+
+impl SuffixPool<'_> {
+    fn insert(&mut self, suffix: Suffix, stats: &mut SolveStats) {
+        let (params, car_config) = (self.params, self.car_config);
+        let Some(key) = suffix.key(params, car_config) else {
+            stats.time_overflows += 1;
+            return;
+        };
+        let node = suffix.nodes[0];
+        let ids = self.at_node.entry(node).or_default();
+        if ids.iter().any(|&id| self.suffixes[id].dominates(&suffix, params, car_config)) {
+            return;
+        }
+        let (suffixes, alive) = (&self.suffixes, &mut self.alive);
+        ids.retain(|&id| {
+            let dominated = suffix.dominates(&suffixes[id], params, car_config);
+            if dominated {
+                alive[id] = false;
+            }
+            !dominated
+        });
+        let id = self.suffixes.len();
+        ids.push(id);
+        self.heap.push(QueuedSuffix { key, node, id });
+        self.suffixes.push(suffix);
+        self.alive.push(true);
+        stats.labels_created += 1;
+        stats.peak_heap = stats.peak_heap.max(self.heap.len());
+    }
+}
+
+struct BackwardSearch {
+    // Non-dominated suffixes by their first node
+    suffixes: HashMap<usize, Vec<Suffix>>,
+    // A budget stopped the search before the split time was reached
+    interrupted: bool,
+}
+
+// Non-dominated suffixes per node, extending only those whose cheapest cost is below `h`
+fn backward_labels(
+    race_data: &RaceData,
+    reversed: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    h: Fixed,
+    budget: &Budget,
+    stats: &mut SolveStats,
+) -> BackwardSearch {
+    let mut pool = SuffixPool {
+        params,
+        car_config,
+        suffixes: Vec::new(),
+        alive: Vec::new(),
+        at_node: HashMap::new(),
+        heap: BinaryHeap::new(),
+    };
+    let finish = Suffix {
+        nodes: vec![params.end_node],
+        pit_stops: Vec::new(),
+        pit_at_head: false,
+        open_distance: Fixed::ZERO,
+        cumulative: Vec::new(),
+        tail: Fixed::ZERO,
+    };
+    pool.insert(finish, stats);
+    let mut interrupted = false;
+
+    while let Some(QueuedSuffix { key, node, id }) = pool.heap.pop() {
+        if !pool.alive[id] || key >= h {
+            continue;
+        }
+        // Nothing may precede the start within a single lap
+        if node == params.start_node {
+            continue;
+        }
+        if budget.exceeded(stats, pool.heap.len()) {
+            interrupted = true;
+            break;
+        }
+        stats.labels_expanded += 1;
+        let suffix = pool.suffixes[id].clone();
+
+        for &(prev, distance) in &reversed.adj[&node] {
+            if prev == params.end_node || distance > car_config.fuel_capacity {
+                continue;
+            }
+            let mut nodes = Vec::with_capacity(suffix.nodes.len() + 1);
+            nodes.push(prev);
+            nodes.extend_from_slice(&suffix.nodes);
+            // Drive `prev -> node` without pitting at `prev`
+            let open = if suffix.pit_at_head {
+                Suffix {
+                    nodes,
+                    pit_stops: suffix.pit_stops.clone(),
+                    pit_at_head: false,
+                    open_distance: distance,
+                    cumulative: vec![distance],
+                    tail: suffix.tail,
+                }
+            } else {
+                let mut cumulative = Vec::with_capacity(suffix.cumulative.len() + 1);
+                cumulative.push(distance);
+                cumulative.extend(suffix.cumulative.iter().map(|&c| c + distance));
+                Suffix {
+                    nodes,
+                    pit_stops: suffix.pit_stops.clone(),
+                    pit_at_head: false,
+                    open_distance: suffix.open_distance + distance,
+                    cumulative,
+                    tail: suffix.tail,
+                }
+            };
+            if open.open_distance > car_config.fuel_capacity {
+                continue;
+            }
+            // Or pit at `prev` first, leaving on a full tank and fresh tyres
+            if race_data.pit_nodes.contains(&prev) {
+                match open.cost(Fixed::ZERO, params, car_config).and_then(|cost| params.pit_stop_penalty.checked_add(cost)) {
+                    Some(tail) => {
+                        let mut pit_stops = vec![prev];
+                        pit_stops.extend_from_slice(&open.pit_stops);
+                        let pitted = Suffix {
+                            nodes: open.nodes.clone(),
+                            pit_stops,
+                            pit_at_head: true,
+                            open_distance: Fixed::ZERO,
+                            cumulative: Vec::new(),
+                            tail,
+                        };
+                        pool.insert(pitted, stats);
+                    }
+                    None => stats.time_overflows += 1,
+                }
+            }
+            pool.insert(open, stats);
+        }
+    }
+
+    let mut by_node: HashMap<usize, Vec<Suffix>> = HashMap::new();
+    for (id, suffix) in pool.suffixes.into_iter().enumerate() {
+        if pool.alive[id] {
+            by_node.entry(suffix.nodes[0]).or_default().push(suffix);
+        }
+    }
+    BackwardSearch { suffixes: by_node, interrupted }
+}
+
+struct Candidate {
+    total_time: Fixed,
+    nodes: Vec<usize>,
+    pit_stops: Vec<usize>,
+}
+
+impl Candidate {
+    fn into_path_info(self, race_data: &RaceData, graph: &Graph, car_config: &CarConfig, params: &RaceParams) -> PathInfo {
+        let pit_stops: Vec<(u32, usize)> = self.pit_stops.into_iter().map(|node| (0, node)).collect();
+        let (segments, _) = crate::verify::replay(race_data, graph, car_config, params, &self.nodes, &pit_stops);
+        debug_assert_eq!(segments.last().map(|s| s.cumulative_time).unwrap_or(Fixed::ZERO), self.total_time);
+        PathInfo { total_time: self.total_time, pit_stops, node_sequence: self.nodes, segments }
+    }
+}
+
+// Cheapest combination of a forward state and a suffix meeting at the same node
+fn join(
+    race_data: &RaceData,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    forward: &HashMap<State, ForwardLabel>,
+    backward: &HashMap<usize, Vec<Suffix>>,
+    stats: &mut SolveStats,
+) -> Option<Candidate> {
+    let mut best: Option<(Fixed, &State, &Suffix)> = None;
+    for (state, label) in forward {
+        let Some(suffixes) = backward.get(&state.current_node) else { continue };
+        for suffix in suffixes {
+            if suffix.pit_at_head && !race_data.pit_nodes.contains(&state.current_node) {
+                continue;
+            }
+            if !suffix.pit_at_head && state.current_fuel < suffix.open_distance {
+                continue;
+            }
+            let total = suffix.cost(state.tyre_distance, params, car_config).and_then(|cost| label.time.checked_add(cost));
+            let Some(total) = total else {
+                stats.time_overflows += 1;
+                continue;
+            };
+            let better = match &best {
+                None => true,
+                // Ties go to the lexicographically smaller route so results are deterministic
+                Some((time, best_state, best_suffix)) => {
+                    total < *time
+                        || (total == *time
+                            && (&forward[*best_state].path, &best_suffix.nodes) > (&label.path, &suffix.nodes))
+                }
+            };
+            if better {
+                best = Some((total, state, suffix));
+            }
+        }
+    }
+    best.map(|(total_time, state, suffix)| {
+        let label = &forward[state];
+        let mut nodes = label.path.clone();
+        nodes.extend_from_slice(&suffix.nodes[1..]);
+        let mut pit_stops = label.pit_stops.clone();
+        pit_stops.extend_from_slice(&suffix.pit_stops);
+        Candidate { total_time, nodes, pit_stops }
+    })
+}
@@ -0,0 +1,195 @@
+use colored::*;
+use crate::visual::ProfilePoint;
+
+// Block heights of a sparkline, lowest first
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// Glyph of each car on an overlaid chart, so cars stay apart without colour
+pub const GLYPHS: [char; 6] = ['●', '■', '▲', '◆', '+', 'x'];
+
+// What a chart plots against race distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Fuel,
+    TyreWear,
+    Time,
+}
+
+impl Metric {
+    pub fn title(self) -> &'static str {
+        match self {
+            Metric::Fuel => "Fuel level",
+            Metric::TyreWear => "Tyre wear",
+            Metric::Time => "Race time",
+        }
+    }
+
+    fn value(self, point: &ProfilePoint) -> f64 {
+        match self {
+            Metric::Fuel => point.fuel.to_f64(),
+            Metric::TyreWear => point.tyre_wear.to_f64(),
+            Metric::Time => point.time.to_f64(),
+        }
+    }
+}
+
+// One car's line on a chart, with the distances where its laps start and it pitted
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub name: String,
+    pub color: Color,
+    pub glyph: char,
+    pub points: Vec<(f64, f64)>,
+    pub laps: Vec<f64>,
+    pub pits: Vec<f64>,
+}
+
+impl Trace {
+    pub fn new(name: String, color: Color, glyph: char, profile: &[ProfilePoint], metric: Metric) -> Self {
+        let points = profile.iter().map(|p| (p.distance.to_f64(), metric.value(p))).collect();
+        let laps = profile.windows(2).filter(|w| w[1].lap > w[0].lap).map(|w| w[0].distance.to_f64()).collect();
+        let pits = profile.iter().filter(|p| p.pit_stop).map(|p| p.distance.to_f64()).collect();
+        Trace { name, color, glyph, points, laps, pits }
+    }
+
+    fn max_distance(&self) -> f64 {
+        self.points.last().map_or(0.0, |&(x, _)| x)
+    }
+
+    fn max_value(&self) -> f64 {
+        self.points.iter().fold(0.0, |max, &(_, y)| y.max(max))
+    }
+}
+
+// The value at distance `x`, linear between points; just after a jump where two points share a distance
+pub fn value_at(points: &[(f64, f64)], x: f64) -> f64 {
+    let Some(i) = points.iter().rposition(|&(px, _)| px <= x) else {
+        return points.first().map_or(0.0, |&(_, y)| y);
+    };
+    match points.get(i + 1) {
+        Some(&(x2, y2)) if x2 > points[i].0 => {
+            let (x1, y1) = points[i];
+            y1 + (y2 - y1) * (x - x1) / (x2 - x1)
+        }
+        _ => points[i].1,
+    }
+}
+
+// Distance covered by column `column` of `width` columns spanning 0..=`x_max`
+fn distance_at(column: usize, width: usize, x_max: f64) -> f64 {
+    if width > 1 { x_max * column as f64 / (width - 1) as f64 } else { 0.0 }
+}
+
+// Column of distance `x` on `width` columns spanning 0..=`x_max`
+fn column_of(x: f64, width: usize, x_max: f64) -> usize {
+    if x_max > 0.0 { ((x / x_max * (width - 1) as f64).round() as usize).min(width - 1) } else { 0 }
+}
+
+// The trace as `width` block characters from 0 (▁) to `y_max` (█)
+pub fn sparkline(trace: &Trace, width: usize, x_max: f64, y_max: f64) -> String {
+    (0..width)
+        .map(|c| {
+            let y = value_at(&trace.points, distance_at(c, width, x_max));
+            let level = if y_max > 0.0 { (y / y_max * (BARS.len() - 1) as f64).round() as usize } else { 0 };
+            BARS[level.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
+// A row under a chart: `┊` where a lap starts and `P` where the car pitted
+pub fn marker_row(trace: &Trace, width: usize, x_max: f64) -> String {
+    let mut row = vec![' '; width];
+    for &x in &trace.laps {
+        row[column_of(x, width, x_max)] = '┊';
+    }
+    for &x in &trace.pits {
+        row[column_of(x, width, x_max)] = 'P';
+    }
+    row.into_iter().collect::<String>().trim_end().to_string()
+}
+
+// One car's fuel, tyre wear and race time as sparklines over its race distance, then its lap and
+// pit markers and the distance axis
+pub fn sparklines(traces: &[(Metric, Trace)], width: usize) -> Vec<String> {
+    let x_max = traces.iter().fold(0.0, |max: f64, (_, t)| t.max_distance().max(max));
+    let mut lines = Vec::new();
+    for (metric, trace) in traces {
+        let y_max = trace.max_value();
+        let last = trace.points.last().map_or(0.0, |&(_, y)| y);
+        lines.push(format!("{:<10} {}  max {:.1}, end {:.1}", metric.title(), sparkline(trace, width, x_max, y_max).color(trace.color), y_max, last));
+    }
+    if let Some((_, trace)) = traces.first() {
+        lines.push(format!("{:<10} {}", "", marker_row(trace, width, x_max)));
+    }
+    lines.push(format!("{:<10} {}", "", distance_axis(width, x_max)));
+    lines
+}
+
+fn distance_axis(width: usize, x_max: f64) -> String {
+    let end = format!("{:.1}", x_max);
+    format!("0{:>w$}", end, w = width.saturating_sub(1))
+}
+
+// Several cars' traces overlaid on one `width` × `height` chart against race distance, each drawn
+// with its glyph in its colour (`*` where cars meet), a dashed line at `threshold` if given, then
+// a row of lap (`┊`) and pit (`P`) markers per car
+pub fn overlay_chart(metric: Metric, traces: &[Trace], width: usize, height: usize, threshold: Option<f64>) -> Vec<String> {
+    let x_max = traces.iter().fold(0.0, |max: f64, t| t.max_distance().max(max));
+    let y_max = traces.iter().fold(threshold.unwrap_or(0.0), |max: f64, t| t.max_value().max(max));
+    let row_of = |y: f64| {
+        let level = if y_max > 0.0 { (y / y_max * (height - 1) as f64).round() as usize } else { 0 };
+        height - 1 - level.min(height - 1)
+    };
+
+    let mut grid: Vec<Vec<(char, Option<Color>)>> = vec![vec![(' ', None); width]; height];
+    if let Some(threshold) = threshold {
+        for cell in &mut grid[row_of(threshold)] {
+            *cell = ('╌', None);
+        }
+    }
+    for trace in traces {
+        let rows: Vec<usize> = (0..=column_of(trace.max_distance(), width, x_max)).map(|c| row_of(value_at(&trace.points, distance_at(c, width, x_max)))).collect();
+        for (c, &row) in rows.iter().enumerate() {
+            // A steep climb or a pit stop's jump is joined up with a vertical line
+            if let Some(&previous) = c.checked_sub(1).and_then(|p| rows.get(p))
+                && row.abs_diff(previous) > 1
+            {
+                for cells in &mut grid[row.min(previous) + 1..row.max(previous)] {
+                    cells[c] = ('│', Some(trace.color));
+                }
+            }
+            // Where cars' lines cross or run together neither glyph wins
+            let shared = matches!(grid[row][c], (glyph, Some(_)) if glyph != trace.glyph && glyph != '│');
+            grid[row][c] = if shared { ('*', Some(Color::White)) } else { (trace.glyph, Some(trace.color)) };
+        }
+    }
+
+    let mut lines = vec![format!("{} vs. race distance", metric.title())];
+    for (r, cells) in grid.iter().enumerate() {
+        let label = if r == 0 {
+            format!("{:>8.1} ┤", y_max)
+        } else if r == height - 1 {
+            format!("{:>8.1} ┤", 0.0)
+        } else if threshold.is_some_and(|t| row_of(t) == r) {
+            format!("{:>8.1} ┤", threshold.unwrap_or_default())
+        } else {
+            format!("{:>8} │", "")
+        };
+        let plot: String = cells
+            .iter()
+            .map(|&(ch, color)| match color {
+                Some(color) => ch.to_string().color(color).to_string(),
+                None if ch == ' ' => " ".to_string(),
+                None => ch.to_string().dimmed().to_string(),
+            })
+            .collect();
+        lines.push(format!("{}{}", label, plot).trim_end().to_string());
+    }
+    lines.push(format!("{:>8} └{}", "", "─".repeat(width)));
+    lines.push(format!("{:>8}  {}", "", distance_axis(width, x_max)));
+    for trace in traces {
+        let name = format!("{:>8}", trace.name).color(trace.color);
+        lines.push(format!("{} {}{}", name, trace.glyph.to_string().color(trace.color), marker_row(trace, width, x_max)).trim_end().to_string());
+    }
+    lines
+}
@@ -0,0 +1,164 @@
+use crate::graph_rcspp::{PathInfo, Segment};
+use crate::units::Fixed;
+
+// Which of the two strategies drove a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Both,
+    OnlyCar,
+    OnlyReference,
+}
+
+// One segment's share of a car's gap to the reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+    pub lap: u32,
+    pub from: usize,
+    pub to: usize,
+    pub route: Route,
+    // The car's time on it minus the reference's, and the parts of that from pit stops and tyre penalties
+    pub delta: Fixed,
+    pub pit: Fixed,
+    pub tyre: Fixed,
+}
+
+// One lap of a car set against the same lap of the reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct LapDelta {
+    pub lap: u32,
+    pub reference_time: Fixed,
+    pub time: Fixed,
+    // This lap's time minus the reference's, and the gap to the reference at the end of the lap
+    pub delta: Fixed,
+    pub gap: Fixed,
+    // Nodes pitted at on this lap
+    pub reference_pits: Vec<usize>,
+    pub pits: Vec<usize>,
+}
+
+impl LapDelta {
+    pub fn pits_differ(&self) -> bool {
+        self.pits != self.reference_pits
+    }
+}
+
+// A car's strategy against the reference's
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarComparison {
+    pub car: usize,
+    pub gap: Fixed,
+    pub laps: Vec<LapDelta>,
+    // Every segment whose time differs, largest difference first; they add up to `gap`
+    pub contributions: Vec<Contribution>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    // The fastest car, which every other car is compared against
+    pub reference: usize,
+    pub reference_time: Fixed,
+    pub cars: Vec<CarComparison>,
+}
+
+// Compares every strategy with the fastest one, lap by lap and segment by segment; `None` with
+// fewer than two strategies
+pub fn compare_strategies(strategies: &[(usize, &PathInfo)]) -> Option<Comparison> {
+    if strategies.len() < 2 {
+        return None;
+    }
+    let &(reference, fastest) = strategies.iter().min_by_key(|(car, p)| (p.total_time, *car))?;
+    let cars = strategies.iter().filter(|&&(car, _)| car != reference).map(|&(car, p)| compare_pair(car, p, fastest)).collect();
+    Some(Comparison { reference, reference_time: fastest.total_time, cars })
+}
+
+// Time spent in the pit lane penalty on a segment: what is left after distance and tyre penalty
+fn pit_time(segment: &Segment) -> Fixed {
+    segment.time - segment.distance - segment.tyre_penalty
+}
+
+fn compare_pair(car: usize, path_info: &PathInfo, reference: &PathInfo) -> CarComparison {
+    let laps = path_info.segments.iter().chain(&reference.segments).map(|s| s.lap + 1).max().unwrap_or(0);
+    let mut lap_deltas = Vec::new();
+    let mut contributions = Vec::new();
+    let mut gap = Fixed::ZERO;
+    for lap in 0..laps {
+        let ours: Vec<&Segment> = path_info.segments.iter().filter(|s| s.lap == lap).collect();
+        let theirs: Vec<&Segment> = reference.segments.iter().filter(|s| s.lap == lap).collect();
+        let time: Fixed = ours.iter().map(|s| s.time).sum();
+        let reference_time: Fixed = theirs.iter().map(|s| s.time).sum();
+        gap += time - reference_time;
+        let pits = |segments: &[&Segment]| segments.iter().filter(|s| s.pit_stop).map(|s| s.from).collect();
+        lap_deltas.push(LapDelta { lap, reference_time, time, delta: time - reference_time, gap, reference_pits: pits(&theirs), pits: pits(&ours) });
+
+        // Pair the reference's segments with the car's over the same edge, in driving order
+        let mut matched = vec![false; ours.len()];
+        for theirs in &theirs {
+            let contribution = match (0..ours.len()).find(|&k| !matched[k] && ours[k].from == theirs.from && ours[k].to == theirs.to) {
+                Some(k) => {
+                    matched[k] = true;
+                    let ours = ours[k];
+                    Contribution {
+                        lap,
+                        from: ours.from,
+                        to: ours.to,
+                        route: Route::Both,
+                        delta: ours.time - theirs.time,
+                        pit: pit_time(ours) - pit_time(theirs),
+                        tyre: ours.tyre_penalty - theirs.tyre_penalty,
+                    }
+                }
+                None => Contribution {
+                    lap,
+                    from: theirs.from,
+                    to: theirs.to,
+                    route: Route::OnlyReference,
+                    delta: Fixed::ZERO - theirs.time,
+                    pit: Fixed::ZERO - pit_time(theirs),
+                    tyre: Fixed::ZERO - theirs.tyre_penalty,
+                },
+            };
+            contributions.push(contribution);
+        }
+        for (ours, _) in ours.iter().zip(&matched).filter(|(_, m)| !**m) {
+            contributions.push(Contribution {
+                lap,
+                from: ours.from,
+                to: ours.to,
+                route: Route::OnlyCar,
+                delta: ours.time,
+                pit: pit_time(ours),
+                tyre: ours.tyre_penalty,
+            });
+        }
+    }
+    contributions.retain(|c| c.delta != Fixed::ZERO);
+    contributions.sort_by_key(|c| (std::cmp::Reverse(c.delta.raw().abs()), c.lap, c.from, c.to));
+    CarComparison { car, gap, laps: lap_deltas, contributions }
+}
+
+// A time difference with its sign, e.g. `+12.5` or `-3`
+pub fn signed(value: Fixed) -> String {
+    if value >= Fixed::ZERO { format!("+{}", value) } else { value.to_string() }
+}
+
+// Nodes pitted at, e.g. `P6 P2`, or `-` for none
+pub fn pit_list(pits: &[usize]) -> String {
+    if pits.is_empty() { "-".to_string() } else { pits.iter().map(|p| format!("P{}", p)).collect::<Vec<_>>().join(" ") }
+}
+
+// Why a segment's time differs, e.g. "pit stop +60, tyre penalty +26.6" or "only car 2 drives it"
+pub fn describe(contribution: &Contribution, car: usize, reference: usize) -> String {
+    let mut parts = Vec::new();
+    match contribution.route {
+        Route::OnlyCar => parts.push(format!("only car {} drives it", car + 1)),
+        Route::OnlyReference => parts.push(format!("only car {} drives it", reference + 1)),
+        Route::Both => {}
+    }
+    if contribution.pit != Fixed::ZERO {
+        parts.push(format!("pit stop {}", signed(contribution.pit)));
+    }
+    if contribution.tyre != Fixed::ZERO {
+        parts.push(format!("tyre penalty {}", signed(contribution.tyre)));
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,292 @@
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt;
+use crate::graph_rcspp::{Graph, RaceParams};
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+
+// Most (lap, node) states the checks unroll the track into; longer races are reported as
+// `Cause::TooManyLaps` instead of being allocated
+pub const MAX_LAP_STATES: usize = 1 << 24;
+
+// One reason why no race strategy exists for a car
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cause {
+    // Start or end node is not part of the track
+    InvalidNode { node: usize },
+    // A pit node index that does not exist on the track
+    InvalidPitNode { node: usize },
+    // The race has no laps to complete
+    ZeroLaps,
+    // Too many laps of this track to unroll within `MAX_LAP_STATES`
+    TooManyLaps { laps: u32, nodes: usize },
+    // An edge longer than a full tank, so no amount of fuel can cover it
+    EdgeTooLong { u: usize, v: usize, distance: Fixed, fuel_capacity: Fixed },
+    // The finish cannot be reached from the start even with unlimited fuel; `reachable` lists the
+    // nodes the car can reach on the final lap, empty if it never gets that far
+    FinishUnreachable { start: usize, end: usize, laps: u32, reachable: Vec<usize> },
+    // The car runs dry leaving `from`: the last refuel was at `refuelled_at` and no pit node lies within range
+    OutOfFuelRange { refuelled_at: usize, from: usize, to: usize, distance: Fixed, fuel_available: Fixed },
+    // The exact problem is feasible but the chosen fuel/tyre resolution rounds it away
+    CoarseResolution { fuel_step: Fixed, tyre_step: Fixed },
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cause::InvalidNode { node } => write!(f, "node {} is not on the track", node),
+            Cause::InvalidPitNode { node } => write!(f, "pit node {} is not on the track", node),
+            Cause::ZeroLaps => write!(f, "the race has zero laps"),
+            Cause::TooManyLaps { laps, nodes } => write!(
+                f,
+                "{} laps of a {} node track is too long to check; at most {} lap and node pairs are supported",
+                laps, nodes, MAX_LAP_STATES
+            ),
+            Cause::EdgeTooLong { u, v, distance, fuel_capacity } => write!(
+                f,
+                "edge {} -> {} is {} long but a full tank only covers {}",
+                u, v, distance, fuel_capacity
+            ),
+            Cause::FinishUnreachable { start, end, laps, reachable } if reachable.is_empty() => write!(
+                f,
+                "node {} cannot be reached from node {} over {} lap(s); the car never gets back to node {} to start lap {}",
+                end, start, laps, start, laps
+            ),
+            Cause::FinishUnreachable { start, end, laps, reachable } => write!(
+                f,
+                "node {} cannot be reached from node {} over {} lap(s); nodes reachable on lap {}: {:?}",
+                end, start, laps, laps, reachable
+            ),
+            Cause::OutOfFuelRange { refuelled_at, from, to, distance, fuel_available } => write!(
+                f,
+                "after filling up at node {} the car reaches node {} with at most {} fuel, but edge {} -> {} needs {} and there is no pit node within range",
+                refuelled_at, from, fuel_available, from, to, distance
+            ),
+            Cause::CoarseResolution { fuel_step, tyre_step } => write!(
+                f,
+                "the race is feasible, but fuel step {} / tyre step {} rounds away every strategy",
+                fuel_step, tyre_step
+            ),
+        }
+    }
+}
+
+// Every cause found for an infeasible race, in the order they were checked
+#[derive(Debug, Clone, Default)]
+pub struct Diagnosis {
+    pub causes: Vec<Cause>,
+}
+
+impl Diagnosis {
+    // Nodes named by any cause, for highlighting
+    pub fn nodes(&self) -> Vec<usize> {
+        let mut nodes = Vec::new();
+        for cause in &self.causes {
+            match cause {
+                Cause::InvalidNode { .. } | Cause::InvalidPitNode { .. } => {}
+                Cause::FinishUnreachable { start, end, .. } => nodes.extend([*start, *end]),
+                Cause::OutOfFuelRange { refuelled_at, from, .. } => nodes.extend([*refuelled_at, *from]),
+                Cause::EdgeTooLong { .. } | Cause::ZeroLaps | Cause::TooManyLaps { .. } | Cause::CoarseResolution { .. } => {}
+            }
+        }
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    // Edges named by any cause, for highlighting
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::new();
+        for cause in &self.causes {
+            match cause {
+                Cause::EdgeTooLong { u, v, .. } => edges.push((*u, *v)),
+                Cause::OutOfFuelRange { from, to, .. } => edges.push((*from, *to)),
+                _ => {}
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+}
+
+// Explains why `car_config` has no feasible strategy. Fuel is the only resource that can make
+// a race infeasible (tyre wear only costs time), so the checks ignore time and tyres entirely.
+pub fn diagnose(race_data: &RaceData, graph: &Graph, car_config: &CarConfig, params: &RaceParams) -> Diagnosis {
+    let mut causes = Vec::new();
+    let n = race_data.n;
+
+    // Conflicting race parameters make every other check meaningless
+    for node in [params.start_node, params.end_node] {
+        if node >= n {
+            causes.push(Cause::InvalidNode { node });
+        }
+    }
+    for &node in &race_data.pit_nodes {
+        if node >= n {
+            causes.push(Cause::InvalidPitNode { node });
+        }
+    }
+    if params.total_laps == 0 {
+        causes.push(Cause::ZeroLaps);
+    }
+    if !causes.is_empty() {
+        return Diagnosis { causes };
+    }
+
+    for e in &race_data.edges {
+        if e.distance > car_config.fuel_capacity {
+            causes.push(Cause::EdgeTooLong { u: e.u, v: e.v, distance: e.distance, fuel_capacity: car_config.fuel_capacity });
+        }
+    }
+
+    let Some(expanded) = LapGraph::new(graph, n, params.start_node, params.total_laps) else {
+        causes.push(Cause::TooManyLaps { laps: params.total_laps, nodes: n });
+        return Diagnosis { causes };
+    };
+    let finish = expanded.index(params.total_laps - 1, params.end_node);
+
+    // Plain reachability over (lap, node) states, ignoring fuel
+    let reachable = expanded.reachable_from(expanded.index(0, params.start_node));
+    if !reachable[finish] {
+        // The finish is only a finish on the final lap, so that is the lap worth listing
+        let final_lap = expanded.index(params.total_laps - 1, 0);
+        let nodes: Vec<usize> = (0..n).filter(|&node| reachable[final_lap + node]).collect();
+        causes.push(Cause::FinishUnreachable {
+            start: params.start_node,
+            end: params.end_node,
+            laps: params.total_laps,
+            reachable: nodes,
+        });
+        return Diagnosis { causes };
+    }
+
+    // Most fuel the car can hold on arrival at each (lap, node), refuelling at every pit node
+    let fuel = expanded.max_fuel(race_data, car_config.fuel_capacity, params.start_node);
+    if fuel[finish].is_none() {
+        // Only stretches that still lead to the finish are worth naming
+        let leads_to_finish = expanded.can_reach(finish);
+        for state in 0..fuel.len() {
+            let Some((available, refuelled_at)) = fuel[state] else { continue };
+            let (lap, u) = ((state / n) as u32, state % n);
+            let departure = if race_data.pit_nodes.contains(&u) { car_config.fuel_capacity } else { available };
+            for &(v, distance) in &graph.adj[&u] {
+                let Some(next) = expanded.step(lap, u, v) else { continue };
+                if distance > departure && distance <= car_config.fuel_capacity && fuel[next].is_none() && leads_to_finish[next] {
+                    causes.push(Cause::OutOfFuelRange { refuelled_at, from: u, to: v, distance, fuel_available: departure });
+                }
+            }
+        }
+        causes.dedup();
+    } else if !params.resolution.is_exact() {
+        causes.push(Cause::CoarseResolution { fuel_step: params.resolution.fuel, tyre_step: params.resolution.tyre });
+    }
+
+    Diagnosis { causes }
+}
+
+// Whether fuel allows the car to reach the finish at all, ignoring time. This is the question
+// `diagnose` answers in detail, without solving the race. `None` if the race has more laps than
+// can be checked (see `Cause::TooManyLaps`).
+pub fn can_finish(race_data: &RaceData, graph: &Graph, car_config: &CarConfig, params: &RaceParams) -> Option<bool> {
+    let n = race_data.n;
+    if params.start_node >= n || params.end_node >= n || params.total_laps == 0 {
+        return Some(false);
+    }
+    let expanded = LapGraph::new(graph, n, params.start_node, params.total_laps)?;
+    let finish = expanded.index(params.total_laps - 1, params.end_node);
+    Some(expanded.max_fuel(race_data, car_config.fuel_capacity, params.start_node)[finish].is_some())
+}
+
+// The track unrolled by lap, following the solver's rule that entering the start node begins a new lap
+struct LapGraph<'a> {
+    graph: &'a Graph,
+    n: usize,
+    start: usize,
+    laps: u32,
+    // `n * laps`, the number of (lap, node) states
+    states: usize,
+}
+
+impl<'a> LapGraph<'a> {
+    fn new(graph: &'a Graph, n: usize, start: usize, laps: u32) -> Option<Self> {
+        let states = n.checked_mul(laps as usize).filter(|&states| states <= MAX_LAP_STATES)?;
+        Some(LapGraph { graph, n, start, laps, states })
+    }
+
+    fn index(&self, lap: u32, node: usize) -> usize {
+        lap as usize * self.n + node
+    }
+
+    fn step(&self, lap: u32, u: usize, v: usize) -> Option<usize> {
+        let next_lap = if v == self.start && u != self.start { lap + 1 } else { lap };
+        (next_lap < self.laps).then(|| self.index(next_lap, v))
+    }
+
+    fn successors(&self, state: usize) -> impl Iterator<Item = (usize, Fixed)> + '_ {
+        let (lap, u) = ((state / self.n) as u32, state % self.n);
+        self.graph.adj[&u].iter().filter_map(move |&(v, d)| self.step(lap, u, v).map(|next| (next, d)))
+    }
+
+    fn reachable_from(&self, source: usize) -> Vec<bool> {
+        let mut seen = vec![false; self.states];
+        let mut queue = VecDeque::from([source]);
+        seen[source] = true;
+        while let Some(state) = queue.pop_front() {
+            for (next, _) in self.successors(state) {
+                if !seen[next] {
+                    seen[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    // States from which `target` can be reached, ignoring fuel
+    fn can_reach(&self, target: usize) -> Vec<bool> {
+        let mut reverse = vec![Vec::new(); self.states];
+        for state in 0..self.states {
+            for (next, _) in self.successors(state) {
+                reverse[next].push(state);
+            }
+        }
+        let mut seen = vec![false; self.states];
+        let mut queue = VecDeque::from([target]);
+        seen[target] = true;
+        while let Some(state) = queue.pop_front() {
+            for &prev in &reverse[state] {
+                if !seen[prev] {
+                    seen[prev] = true;
+                    queue.push_back(prev);
+                }
+            }
+        }
+        seen
+    }
+
+    // Widest-path search for the maximum arrival fuel per state, with the node it was last refuelled at
+    fn max_fuel(&self, race_data: &RaceData, capacity: Fixed, start: usize) -> Vec<Option<(Fixed, usize)>> {
+        let mut best: Vec<Option<(Fixed, usize)>> = vec![None; self.states];
+        let source = self.index(0, start);
+        best[source] = Some((capacity, start));
+        let mut heap = BinaryHeap::from([(capacity, source, start)]);
+        while let Some((fuel, state, refuelled_at)) = heap.pop() {
+            if best[state].is_some_and(|(f, _)| f > fuel) {
+                continue;
+            }
+            let node = state % self.n;
+            let (departure, origin) = if race_data.pit_nodes.contains(&node) { (capacity, node) } else { (fuel, refuelled_at) };
+            for (next, distance) in self.successors(state) {
+                if distance > departure {
+                    continue;
+                }
+                let arrival = departure - distance;
+                if best[next].is_none_or(|(f, _)| arrival > f) {
+                    best[next] = Some((arrival, origin));
+                    heap.push((arrival, next, origin));
+                }
+            }
+        }
+        best
+    }
+}
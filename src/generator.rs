@@ -0,0 +1,266 @@
+use std::io;
+use std::str::FromStr;
+use std::f64::consts::PI;
+use crate::input_parser::{self, CarConfig, Edge, Position, RaceData, MAX_NODES, MAX_VALUE};
+use crate::units::Fixed;
+
+// Small deterministic xorshift generator so every track is reproducible from its seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Splitmix the seed so nearby seeds give unrelated tracks, and never start from zero
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Uniform in `lo..=hi`
+    pub fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.next_u64() % (hi - lo + 1)
+    }
+
+    // Uniform in `lo..=hi`, in steps of 0.1
+    pub fn distance(&mut self, lo: Fixed, hi: Fixed) -> Fixed {
+        let (lo, hi) = (lo.raw().max(0) / 100, hi.raw().max(0) / 100);
+        Fixed::from_raw(self.range(lo as u64, (hi.max(lo)) as u64) as i64 * 100)
+    }
+}
+
+// Layout of the main line, before shortcuts and pits are added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    // A single loop
+    Ring,
+    // Rows of nodes linked right and down, with the far corner looping back to the start
+    Grid,
+    // A loop where some corners can be cut on a faster racing line
+    Circuit,
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ring" => Ok(Shape::Ring),
+            "grid" => Ok(Shape::Grid),
+            "circuit" => Ok(Shape::Circuit),
+            _ => Err(format!("unknown shape '{}' (expected ring, grid or circuit)", s)),
+        }
+    }
+}
+
+// Where pit stops are allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitPlacement {
+    // Evenly spaced along the main line
+    Spaced,
+    // At random nodes of the main line
+    Random,
+    // On extra pit lane nodes, each a detour around one edge of the main line
+    Lane,
+}
+
+impl FromStr for PitPlacement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spaced" => Ok(PitPlacement::Spaced),
+            "random" => Ok(PitPlacement::Random),
+            "lane" => Ok(PitPlacement::Lane),
+            _ => Err(format!("unknown pit placement '{}' (expected spaced, random or lane)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackSpec {
+    // Total node count, including any pit lane nodes
+    pub nodes: usize,
+    pub shape: Shape,
+    // Extra edges that skip ahead along the main line
+    pub shortcuts: usize,
+    pub pits: usize,
+    pub pit_placement: PitPlacement,
+    // Range of a single main line edge
+    pub min_distance: Fixed,
+    pub max_distance: Fixed,
+    pub cars: Vec<CarConfig>,
+    pub seed: u64,
+}
+
+impl Default for TrackSpec {
+    fn default() -> Self {
+        TrackSpec {
+            nodes: 20,
+            shape: Shape::Circuit,
+            shortcuts: 2,
+            pits: 2,
+            pit_placement: PitPlacement::Spaced,
+            min_distance: Fixed::from_int(5),
+            max_distance: Fixed::from_int(15),
+            cars: vec![CarConfig { fuel_capacity: Fixed::from_int(100), tyre_cost: Fixed::from_int(2) }],
+            seed: 0,
+        }
+    }
+}
+
+// Checks that `spec` stays within the limits `input_parser::validate` puts on a track: at most
+// `MAX_NODES` nodes, and distances, fuel capacities and tyre costs within 0..=`MAX_VALUE`
+pub fn check_spec(spec: &TrackSpec) -> io::Result<()> {
+    let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    if spec.nodes > MAX_NODES {
+        return invalid(format!("{} nodes is more than the limit of {}", spec.nodes, MAX_NODES));
+    }
+    if spec.min_distance < Fixed::ZERO || spec.max_distance > MAX_VALUE {
+        return invalid(format!("Edge distances must lie within 0..={}", MAX_VALUE));
+    }
+    for (i, car) in spec.cars.iter().enumerate() {
+        let in_range = |v: Fixed| Fixed::ZERO <= v && v <= MAX_VALUE;
+        if !in_range(car.fuel_capacity) || !in_range(car.tyre_cost) {
+            return invalid(format!("Car {} has a fuel capacity or tyre cost outside 0..={}", i + 1, MAX_VALUE));
+        }
+    }
+    Ok(())
+}
+
+// Builds a track from `spec`. The start is node 0 and the finish the last node, matching the
+// command line's defaults, and the finish always links back to the start so multi-lap races work.
+// Fails when `spec` does not pass `check_spec`; anything returned has passed `validate`.
+pub fn generate(spec: &TrackSpec) -> io::Result<RaceData> {
+    check_spec(spec)?;
+    let mut rng = Rng::new(spec.seed);
+    let lane_pits = if spec.pit_placement == PitPlacement::Lane { spec.pits.min(spec.nodes.saturating_sub(2) / 2) } else { 0 };
+    let main = spec.nodes.saturating_sub(lane_pits).max(2);
+    let n = main + lane_pits;
+    // Main line node k keeps its index, except the finish which moves behind the pit lane nodes
+    let id = |k: usize| if k == main - 1 { n - 1 } else { k };
+    let mut edges = Vec::new();
+    // Corner cuts and shortcuts span several hops, so they are capped at the input limit
+    let mut add = |u: usize, v: usize, distance: Fixed| edges.push(Edge { u, v, distance: distance.min(MAX_VALUE) });
+    // Map position of each main line node k, so the layout matches the distances
+    let mut points = vec![(0.0, 0.0); main];
+
+    match spec.shape {
+        Shape::Ring | Shape::Circuit => {
+            let hops: Vec<Fixed> = (0..main).map(|_| rng.distance(spec.min_distance, spec.max_distance)).collect();
+            for (k, &hop) in hops.iter().enumerate() {
+                add(id(k), id((k + 1) % main), hop);
+            }
+            // Anticlockwise round a circle as long as the lap, each node as far along as its distance
+            let lap: f64 = hops.iter().map(|h| h.to_f64()).sum::<f64>().max(f64::EPSILON);
+            let mut along = 0.0;
+            for (k, point) in points.iter_mut().enumerate() {
+                let angle = 2.0 * PI * along / lap;
+                *point = (lap / (2.0 * PI) * angle.cos(), lap / (2.0 * PI) * angle.sin());
+                along += hops[k].to_f64();
+            }
+            if spec.shape == Shape::Circuit {
+                // Cutting a corner saves up to a fifth of the two edges it replaces
+                for k in 0..main.saturating_sub(2) {
+                    if rng.range(0, 2) == 0 {
+                        let saving = rng.range(0, 20) as i64;
+                        let distance = (hops[k] + hops[k + 1]) * Fixed::from_raw(1000 - saving * 10);
+                        add(id(k), id(k + 2), distance.max(Fixed::EPSILON));
+                    }
+                }
+            }
+        }
+        Shape::Grid => {
+            let width = (main as f64).sqrt().ceil() as usize;
+            let spacing = (spec.min_distance.to_f64() + spec.max_distance.to_f64()) / 2.0;
+            for (k, point) in points.iter_mut().enumerate() {
+                *point = ((k % width) as f64 * spacing, -((k / width) as f64) * spacing);
+            }
+            for k in 0..main {
+                if (k + 1) % width != 0 && k + 1 < main {
+                    add(id(k), id(k + 1), rng.distance(spec.min_distance, spec.max_distance));
+                }
+                if k + width < main {
+                    add(id(k), id(k + width), rng.distance(spec.min_distance, spec.max_distance));
+                }
+            }
+            add(id(main - 1), id(0), rng.distance(spec.min_distance, spec.max_distance));
+        }
+    }
+
+    // Shortcuts skip 2..=5 nodes ahead for well under the distance they save
+    if main > 3 {
+        for _ in 0..spec.shortcuts {
+            let u = rng.range(0, main as u64 - 3) as usize;
+            let v = (u + rng.range(2, 5) as usize).min(main - 1);
+            let per_hop = rng.distance(spec.min_distance, spec.max_distance);
+            let factor = Fixed::from_raw(rng.range(500, 900) as i64);
+            add(id(u), id(v), (per_hop * Fixed::from_int((v - u) as i64) * factor).max(Fixed::EPSILON));
+        }
+    }
+
+    let mut lane_points = Vec::new();
+    // Candidate pit nodes on the main line, never the start or the finish
+    let candidates: Vec<usize> = (1..main - 1).collect();
+    let mut pit_nodes: Vec<usize> = match spec.pit_placement {
+        PitPlacement::Spaced => {
+            let count = spec.pits.min(candidates.len());
+            (0..count).map(|i| candidates[(2 * i + 1) * candidates.len() / (2 * count)]).collect()
+        }
+        PitPlacement::Random => {
+            let mut pool = candidates;
+            let mut chosen = Vec::new();
+            while chosen.len() < spec.pits && !pool.is_empty() {
+                chosen.push(pool.swap_remove(rng.range(0, pool.len() as u64 - 1) as usize));
+            }
+            chosen
+        }
+        PitPlacement::Lane => {
+            // Each pit lane node leaves the main line at an evenly spaced node and rejoins at the next
+            (0..lane_pits)
+                .map(|j| {
+                    let anchor = (2 * j + 1) * (main - 1) / (2 * lane_pits);
+                    let pit = main - 1 + j;
+                    let half = rng.distance(spec.min_distance, spec.max_distance) * Fixed::from_raw(600);
+                    // Drawn beside the edge it bypasses, to the right of the direction of travel
+                    let ((ax, ay), (bx, by)) = (points[anchor], points[(anchor + 1) % main]);
+                    let (dx, dy) = (bx - ax, by - ay);
+                    let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+                    let offset = half.to_f64() * 0.5;
+                    lane_points.push(((ax + bx) / 2.0 + dy / length * offset, (ay + by) / 2.0 - dx / length * offset));
+                    add(id(anchor), pit, half.max(Fixed::EPSILON));
+                    add(pit, id(anchor + 1), half.max(Fixed::EPSILON));
+                    pit
+                })
+                .collect()
+        }
+    };
+    pit_nodes.sort_unstable();
+    pit_nodes.dedup();
+
+    // Main line nodes in index order, then the pit lane nodes, then the finish
+    let mut positions: Vec<Position> = Vec::with_capacity(n);
+    let position = |(x, y): (f64, f64)| Position { x: Fixed::from_f64(x), y: Fixed::from_f64(y) };
+    positions.extend(points[..main - 1].iter().map(|&p| position(p)));
+    positions.extend(lane_points.into_iter().map(position));
+    positions.push(position(points[main - 1]));
+
+    let race_data = RaceData {
+        n,
+        np: pit_nodes.len(),
+        c: spec.cars.len(),
+        m: edges.len(),
+        edges,
+        pit_nodes,
+        cars: spec.cars.clone(),
+        positions: Some(positions),
+    };
+    input_parser::validate(&race_data)?;
+    Ok(race_data)
+}
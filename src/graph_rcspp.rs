@@ -1,30 +1,62 @@
 use std::collections::{BinaryHeap, HashMap};
 use std::cmp::Ordering;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::units::Fixed;
 
 // Represents a state in the RCSPP algorithm
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct State {
     pub lap: u32,
     pub current_node: usize,
-    pub current_fuel: u32,
-    pub tyre_distance: u32,
+    pub current_fuel: Fixed,
+    pub tyre_distance: Fixed,
+}
+
+// One driven edge of a strategy with the car's state either side of it.
+// "Before" values are on arrival at `from`, i.e. before any pit stop taken there.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Segment {
+    // Lap on which the segment is driven
+    pub lap: u32,
+    pub from: usize,
+    pub to: usize,
+    // Refuelled and changed tyres at `from` before driving the segment
+    pub pit_stop: bool,
+    // Length of the edge driven, i.e. the fuel it burns
+    pub distance: Fixed,
+    // Travel time plus tyre penalty plus any pit stop penalty
+    pub time: Fixed,
+    pub cumulative_time: Fixed,
+    pub fuel_before: Fixed,
+    pub fuel_after: Fixed,
+    pub tyre_before: Fixed,
+    pub tyre_after: Fixed,
+    pub tyre_penalty: Fixed,
 }
 
 // Represents information about a path leading to a state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PathInfo {
-    pub total_time: u32,
+    pub total_time: Fixed,
     pub pit_stops: Vec<(u32, usize)>,
     pub node_sequence: Vec<usize>,
+    // Authoritative per-segment timeline, one entry per consecutive pair in `node_sequence`
+    pub segments: Vec<Segment>,
 }
 
 // A label used in the Dijkstra-like algorithm
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Label {
-    pub time: u32,
+    pub time: Fixed,
     pub state: State,
     pub path: Vec<usize>,
     pub pit_stops_taken: Vec<(u32, usize)>,
+    pub segments: Vec<Segment>,
 }
 
 impl Ord for Label {
@@ -40,12 +72,55 @@ impl PartialOrd for Label {
     }
 }
 
+// Step sizes that fuel and tyre wear are snapped to in the search state.
+// Fuel is rounded down and tyre wear up, so every discretised state is still
+// reachable by the real car and the returned strategy stays feasible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    pub fuel: Fixed,
+    pub tyre: Fixed,
+}
+
+impl Resolution {
+    // No discretisation beyond the fixed-point precision itself
+    pub const EXACT: Resolution = Resolution { fuel: Fixed::EPSILON, tyre: Fixed::EPSILON };
+
+    // Splits the car's fuel capacity into at most `buckets` steps and uses the same step for tyre wear,
+    // which can never exceed the distance covered on one tank
+    pub fn buckets(car_config: &crate::input_parser::CarConfig, buckets: u32) -> Resolution {
+        let buckets = buckets.max(1) as i64;
+        let step = Fixed::from_raw(((car_config.fuel_capacity.raw() + buckets - 1) / buckets).max(1));
+        Resolution { fuel: step, tyre: step }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.fuel <= Fixed::EPSILON && self.tyre <= Fixed::EPSILON
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution::EXACT
+    }
+}
+
+// Race parameters shared by every car configuration
+#[derive(Debug, Clone, Copy)]
+pub struct RaceParams {
+    pub start_node: usize,
+    pub end_node: usize,
+    pub total_laps: u32,
+    pub pit_stop_penalty: Fixed,
+    pub tyre_wear_distance: Fixed,
+    pub resolution: Resolution,
+}
+
 pub struct Graph {
-    pub adj: HashMap<usize, Vec<(usize, u32)>>,
+    pub adj: HashMap<usize, Vec<(usize, Fixed)>>,
 }
 
 impl Graph {
-    pub fn new(n: usize, edges: &Vec<crate::input_parser::Edge>) -> Self {
+    pub fn new(n: usize, edges: &[crate::input_parser::Edge]) -> Self {
         let mut adj = HashMap::with_capacity(n);
         for i in 0..n {
             adj.insert(i, Vec::new());
@@ -55,20 +130,292 @@ impl Graph {
         }
         Graph { adj }
     }
+
+    // Same track with every edge pointing the other way, for searching backwards from the finish
+    pub fn reversed(&self) -> Graph {
+        let mut adj: HashMap<usize, Vec<(usize, Fixed)>> = self.adj.keys().map(|&node| (node, Vec::new())).collect();
+        let mut nodes: Vec<&usize> = self.adj.keys().collect();
+        nodes.sort_unstable();
+        for &u in nodes {
+            for &(v, distance) in &self.adj[&u] {
+                adj.entry(v).or_default().push((u, distance));
+            }
+        }
+        Graph { adj }
+    }
+}
+
+// Budgets that stop the search early; `None` leaves that budget unbounded. They are soft limits:
+// budgets are checked before each label is expanded, so the expansion that crosses one can still
+// push a label per outgoing move (at most two per edge) past it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SolveOptions {
+    // Wall-clock limit for a single car's search
+    pub timeout: Option<Duration>,
+    // Maximum number of labels pushed onto the heap
+    pub max_labels: Option<usize>,
+    // Maximum number of labels waiting in the heap at once
+    pub max_heap: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveStatus {
+    // Search completed and the best strategy is optimal
+    Optimal,
+    // A budget was hit after at least one strategy had been found
+    Feasible,
+    // Search completed without reaching the finish line
+    Infeasible,
+    // A budget was hit before any strategy had been found
+    BudgetExceeded,
+}
+
+impl fmt::Display for SolveStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SolveStatus::Optimal => "Optimal",
+            SolveStatus::Feasible => "Feasible",
+            SolveStatus::Infeasible => "Infeasible",
+            SolveStatus::BudgetExceeded => "BudgetExceeded",
+        };
+        f.pad(name)
+    }
+}
+
+// Counters collected while searching
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SolveStats {
+    pub labels_created: usize,
+    pub labels_expanded: usize,
+    pub peak_heap: usize,
+    // Moves dropped because their race time would not fit in `Fixed`
+    pub time_overflows: usize,
+    pub elapsed: Duration,
+}
+
+// Result of a (possibly budget-limited) search
+#[derive(Debug, Clone)]
+pub struct SolveOutcome {
+    pub status: SolveStatus,
+    // Best strategy found so far
+    pub best: Option<PathInfo>,
+    // No strategy can finish faster than this; `None` when the race is infeasible
+    pub lower_bound: Option<Fixed>,
+    pub stats: SolveStats,
+}
+
+impl SolveOutcome {
+    // Keeps any strategy that was found, even if optimality was not proven
+    pub fn into_result(self) -> Result<PathInfo, SolveError> {
+        match (self.status, self.best) {
+            (_, Some(best)) => Ok(best),
+            (SolveStatus::BudgetExceeded, None) => Err(SolveError::BudgetExceeded),
+            (_, None) => Err(SolveError::NoFeasibleStrategy),
+        }
+    }
+}
+
+// Why a car configuration could not be solved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    // Start or end node is not part of the track
+    InvalidNode(usize),
+    // The search finished without reaching the finish line
+    NoFeasibleStrategy,
+    // A solve budget ran out before any strategy was found
+    BudgetExceeded,
+    // Every way to the finish takes longer than the fixed-point times can hold
+    TimeOverflow,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::InvalidNode(node) => write!(f, "node {} is not on the track", node),
+            SolveError::NoFeasibleStrategy => write!(f, "no feasible race strategy found"),
+            SolveError::BudgetExceeded => write!(f, "solve budget exceeded before any strategy was found"),
+            SolveError::TimeOverflow => write!(f, "race time exceeds the largest representable time"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+// Solves one car, checking the race parameters against the track first
+pub fn solve(
+    race_data: &crate::input_parser::RaceData,
+    graph: &Graph,
+    car_config: &crate::input_parser::CarConfig,
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> Result<SolveOutcome, SolveError> {
+    checked_outcome(race_data, params, || find_optimal_path(race_data, graph, car_config, params, options))
+}
+
+// Runs `search` once the race's nodes are known to exist, then reports a race that only
+// overflowed as an error rather than as infeasible
+pub(crate) fn checked_outcome(
+    race_data: &crate::input_parser::RaceData,
+    params: &RaceParams,
+    search: impl FnOnce() -> SolveOutcome,
+) -> Result<SolveOutcome, SolveError> {
+    for node in [params.start_node, params.end_node] {
+        if node >= race_data.n {
+            return Err(SolveError::InvalidNode(node));
+        }
+    }
+    let outcome = search();
+    // Any strategy found is faster than the moves that overflowed, but without one the race is
+    // only out of range, not infeasible
+    if outcome.status == SolveStatus::Infeasible && outcome.stats.time_overflows > 0 {
+        return Err(SolveError::TimeOverflow);
+    }
+    Ok(outcome)
+}
+
+// Solves every car in `race_data` with the same race parameters
+pub fn solve_all(
+    race_data: &crate::input_parser::RaceData,
+    graph: &Graph,
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> Vec<Result<PathInfo, SolveError>> {
+    solve_all_with(race_data, graph, options, |_| *params)
+        .into_iter()
+        .map(|r| r.and_then(SolveOutcome::into_result))
+        .collect()
+}
+
+// Solves every car on a pool of worker threads, with parameters chosen per car.
+// Results are returned in the same order as `race_data.cars`.
+pub fn solve_all_with<F>(
+    race_data: &crate::input_parser::RaceData,
+    graph: &Graph,
+    options: &SolveOptions,
+    params_for: F,
+) -> Vec<Result<SolveOutcome, SolveError>>
+where
+    F: Fn(&crate::input_parser::CarConfig) -> RaceParams + Sync,
+{
+    let cars = &race_data.cars;
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(cars.len());
+    let next_car = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<SolveOutcome, SolveError>>>> = Mutex::new(vec![None; cars.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next_car.fetch_add(1, AtomicOrdering::Relaxed);
+                let Some(car_config) = cars.get(i) else { break };
+                let result = solve(race_data, graph, car_config, &params_for(car_config), options);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.expect("every car is solved by a worker"))
+        .collect()
+}
+
+// Compares a discretised solve against the exact optimum for the same car
+#[derive(Debug, Clone, Copy)]
+pub struct GapReport {
+    // Race time as the discretised search scored it, with tyre wear rounded up
+    pub discretised_time: Option<Fixed>,
+    // The same strategy replayed with exact fuel and tyre wear, i.e. what it really takes
+    pub replayed_time: Option<Fixed>,
+    pub exact_time: Option<Fixed>,
+    // Whether the exact solve finished within the caller's budgets; otherwise `exact_time` is
+    // only the best strategy it found
+    pub exact_status: SolveStatus,
+}
+
+impl GapReport {
+    // Extra race time the discretised strategy really costs against the exact optimum
+    pub fn absolute(&self) -> Option<Fixed> {
+        match (self.replayed_time, self.exact_time) {
+            (Some(r), Some(e)) => Some(r - e),
+            _ => None,
+        }
+    }
+
+    // How much the rounding inflated the discretised time over the strategy's real time
+    pub fn rounding(&self) -> Option<Fixed> {
+        match (self.discretised_time, self.replayed_time) {
+            (Some(d), Some(r)) => Some(d - r),
+            _ => None,
+        }
+    }
+
+    // Gap as a fraction of the exact optimum
+    pub fn relative(&self) -> Option<f64> {
+        match (self.absolute(), self.exact_time) {
+            (Some(gap), Some(e)) if e.is_positive() => Some(gap.to_f64() / e.to_f64()),
+            (Some(_), Some(_)) => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+// Re-solves without discretisation, within the same budgets, to measure how much `discretised`
+// gave away. The discretised strategy is replayed at exact resources first, so the gap is what
+// the route costs rather than what the rounding added to its score.
+pub fn optimality_gap(
+    race_data: &crate::input_parser::RaceData,
+    graph: &Graph,
+    car_config: &crate::input_parser::CarConfig,
+    params: &RaceParams,
+    options: &SolveOptions,
+    discretised: Option<&PathInfo>,
+) -> GapReport {
+    let exact_params = RaceParams { resolution: Resolution::EXACT, ..*params };
+    let exact = find_optimal_path(race_data, graph, car_config, &exact_params, options);
+    // Fuel is rounded down and tyre wear up, so the route stays feasible when replayed exactly
+    let replayed_time = discretised.and_then(|p| {
+        let (timeline, discrepancies) = crate::verify::replay(race_data, graph, car_config, &exact_params, &p.node_sequence, &p.pit_stops);
+        if discrepancies.is_empty() { timeline.last().map(|s| s.cumulative_time) } else { None }
+    });
+    GapReport {
+        discretised_time: discretised.map(|p| p.total_time),
+        replayed_time,
+        exact_time: exact.best.map(|p| p.total_time),
+        exact_status: exact.status,
+    }
+}
+
+// Remembers `label` if it reaches the finish faster than the incumbent
+fn track_incumbent(incumbent: &mut Option<Label>, label: &Label, end_node: usize, total_laps: u32) {
+    let finishes = label.state.current_node == end_node && label.state.lap + 1 == total_laps;
+    if finishes && incumbent.as_ref().is_none_or(|best| label.time < best.time) {
+        *incumbent = Some(label.clone());
+    }
+}
+
+// Penalty for running `tyre_distance` on the current set, charged per unit beyond the wear threshold
+pub(crate) fn tyre_penalty(tyre_distance: Fixed, tyre_wear_distance: Fixed, tyre_cost: Fixed) -> Fixed {
+    if tyre_distance > tyre_wear_distance {
+        (tyre_distance - tyre_wear_distance) * tyre_cost
+    } else {
+        Fixed::ZERO
+    }
 }
 
 pub fn find_optimal_path(
     race_data: &crate::input_parser::RaceData,
     graph: &Graph,
-    start_node: usize,
-    end_node: usize,
-    total_laps: u32,
     car_config: &crate::input_parser::CarConfig,
-    pit_stop_penalty: u32, // Assuming a default pit stop penalty
-    tyre_wear_distance: u32, // Assuming a default tyre wear distance
-) -> Option<PathInfo> {
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> SolveOutcome {
+    let started = Instant::now();
+    let mut stats = SolveStats::default();
+    let RaceParams { start_node, end_node, total_laps, pit_stop_penalty, tyre_wear_distance, resolution } = *params;
     let mut min_heap = BinaryHeap::new();
-    let mut best_times: HashMap<State, u32> = HashMap::new();
+    let mut best_times: HashMap<State, Fixed> = HashMap::new();
     let mut best_paths: HashMap<State, Vec<usize>> = HashMap::new();
     let mut best_pit_stops: HashMap<State, Vec<(u32, usize)>> = HashMap::new();
 
@@ -76,36 +423,58 @@ pub fn find_optimal_path(
     let initial_state = State {
         lap: 0,
         current_node: start_node,
-        current_fuel: car_config.fuel_capacity,
-        tyre_distance: 0,
+        current_fuel: car_config.fuel_capacity.floor_to(resolution.fuel),
+        tyre_distance: Fixed::ZERO,
     };
     let initial_label = Label {
-        time: 0,
+        time: Fixed::ZERO,
         state: initial_state.clone(),
         path: vec![start_node],
         pit_stops_taken: Vec::new(),
+        segments: Vec::new(),
     };
     min_heap.push(initial_label);
-    best_times.insert(initial_state.clone(), 0);
+    stats.labels_created = 1;
+    stats.peak_heap = 1;
+    best_times.insert(initial_state.clone(), Fixed::ZERO);
     best_paths.insert(initial_state.clone(), vec![start_node]);
     best_pit_stops.insert(initial_state, Vec::new());
 
-    let mut overall_min_time = u32::MAX;
+    let mut overall_min_time = Fixed::MAX;
     let mut overall_optimal_path = None;
     let mut overall_optimal_pit_stops = None;
+    let mut overall_optimal_segments = None;
+    // Set when a budget stops the search: the cheapest label still waiting to be expanded
+    let mut interrupted_at = None;
+    // Fastest finish pushed so far, which a budget can stop the search before popping
+    let mut incumbent: Option<Label> = None;
 
     while let Some(label) = min_heap.pop() {
+        // Labels leave the heap in time order, so nothing left can beat the best finish
+        if label.time >= overall_min_time {
+            break;
+        }
+        let over_budget = options.timeout.is_some_and(|t| started.elapsed() >= t)
+            || options.max_labels.is_some_and(|max| stats.labels_created > max)
+            || options.max_heap.is_some_and(|max| min_heap.len() + 1 > max);
+        if over_budget {
+            interrupted_at = Some(label.time);
+            break;
+        }
+        stats.labels_expanded += 1;
+
         let current_time = label.time;
         let current_state = label.state;
         let current_path = label.path;
         let current_pit_stops = label.pit_stops_taken;
+        let current_segments = label.segments;
 
         // Dominance check
         
-        if let Some(&existing_best_time) = best_times.get(&current_state) {
-            if current_time > existing_best_time {
-                continue;
-            }
+        if let Some(&existing_best_time) = best_times.get(&current_state)
+            && current_time > existing_best_time
+        {
+            continue;
         }
         
 
@@ -115,6 +484,7 @@ pub fn find_optimal_path(
                 overall_min_time = current_time;
                 overall_optimal_path = Some(current_path.clone());
                 overall_optimal_pit_stops = Some(current_pit_stops.clone());
+                overall_optimal_segments = Some(current_segments.clone());
             }
             continue; // Continue to explore other paths that might be better for other states
         }
@@ -122,17 +492,19 @@ pub fn find_optimal_path(
         // Explore neighbors
         if let Some(neighbors) = graph.adj.get(&current_state.current_node) {
             for &(neighbor_node, edge_distance) in neighbors {
+                // Crossing the start line after the final lap can never lead to the finish
+                if neighbor_node == start_node && current_state.current_node != start_node && current_state.lap + 1 >= total_laps {
+                    continue;
+                }
+
                 // Case 1: Attempt to move without a pit stop
                 if current_state.current_fuel >= edge_distance {
-                    let new_fuel = current_state.current_fuel - edge_distance;
-                    let new_tyre_distance = current_state.tyre_distance + edge_distance;
+                    let new_fuel = (current_state.current_fuel - edge_distance).floor_to(resolution.fuel);
+                    let new_tyre_distance = (current_state.tyre_distance + edge_distance).ceil_to(resolution.tyre);
                     let travel_time = edge_distance;
-                    let tyre_penalty = if new_tyre_distance > tyre_wear_distance {
-                        (new_tyre_distance - tyre_wear_distance) * car_config.tyre_cost
-                    } else {
-                        0
-                    };
-                    let new_time = current_time + travel_time + tyre_penalty;
+                    let tyre_penalty = tyre_penalty(new_tyre_distance, tyre_wear_distance, car_config.tyre_cost);
+                    let new_time = current_time.checked_add(travel_time).and_then(|t| t.checked_add(tyre_penalty));
+                    stats.time_overflows += new_time.is_none() as usize;
 
                     let new_state = State {
                         lap: if neighbor_node == start_node && current_state.current_node != start_node { current_state.lap + 1 } else { current_state.lap },
@@ -141,15 +513,37 @@ pub fn find_optimal_path(
                         tyre_distance: new_tyre_distance,
                     };
 
-                    if new_time < *best_times.get(&new_state).unwrap_or(&u32::MAX) {
+                    if let Some(new_time) = new_time
+                        && new_time < *best_times.get(&new_state).unwrap_or(&Fixed::MAX)
+                    {
                         let mut new_path = current_path.clone();
                         new_path.push(neighbor_node);
-                        min_heap.push(Label {
+                        let mut new_segments = current_segments.clone();
+                        new_segments.push(Segment {
+                            lap: current_state.lap,
+                            from: current_state.current_node,
+                            to: neighbor_node,
+                            pit_stop: false,
+                            distance: edge_distance,
+                            time: new_time - current_time,
+                            cumulative_time: new_time,
+                            fuel_before: current_state.current_fuel,
+                            fuel_after: new_fuel,
+                            tyre_before: current_state.tyre_distance,
+                            tyre_after: new_tyre_distance,
+                            tyre_penalty,
+                        });
+                        let new_label = Label {
                             time: new_time,
                             state: new_state.clone(),
                             path: new_path.clone(),
                             pit_stops_taken: current_pit_stops.clone(),
-                        });
+                            segments: new_segments,
+                        };
+                        track_incumbent(&mut incumbent, &new_label, end_node, total_laps);
+                        min_heap.push(new_label);
+                        stats.labels_created += 1;
+                        stats.peak_heap = stats.peak_heap.max(min_heap.len());
                         // Re-enable for debugging
                         best_times.insert(new_state.clone(), new_time);
                         best_paths.insert(new_state.clone(), new_path);
@@ -162,18 +556,18 @@ pub fn find_optimal_path(
                 if race_data.pit_nodes.contains(&current_state.current_node) {
                     // After a pit stop, fuel is reset. Check if new fuel is enough for this edge.
                     if car_config.fuel_capacity >= edge_distance {
-                        let new_fuel_after_pit_and_travel = car_config.fuel_capacity - edge_distance;
-                        let new_tyre_distance_after_pit_and_travel = edge_distance; // Tyre resets, then covers edge distance
+                        let new_fuel_after_pit_and_travel = (car_config.fuel_capacity - edge_distance).floor_to(resolution.fuel);
+                        let new_tyre_distance_after_pit_and_travel = edge_distance.ceil_to(resolution.tyre); // Tyre resets, then covers edge distance
                         let travel_time = edge_distance; // Assuming time is equal to distance
 
                         // Calculate tyre wear penalty for this segment after pit
-                        let tyre_penalty = if new_tyre_distance_after_pit_and_travel > tyre_wear_distance {
-                            (new_tyre_distance_after_pit_and_travel - tyre_wear_distance) * car_config.tyre_cost
-                        } else {
-                            0
-                        };
+                        let tyre_penalty = tyre_penalty(new_tyre_distance_after_pit_and_travel, tyre_wear_distance, car_config.tyre_cost);
 
-                        let new_time_after_pit = current_time + pit_stop_penalty + travel_time + tyre_penalty;
+                        let new_time_after_pit = current_time
+                            .checked_add(pit_stop_penalty)
+                            .and_then(|t| t.checked_add(travel_time))
+                            .and_then(|t| t.checked_add(tyre_penalty));
+                        stats.time_overflows += new_time_after_pit.is_none() as usize;
 
                         let new_state = State {
                             lap: if neighbor_node == start_node && current_state.current_node != start_node { current_state.lap + 1 } else { current_state.lap },
@@ -182,17 +576,39 @@ pub fn find_optimal_path(
                             tyre_distance: new_tyre_distance_after_pit_and_travel,
                         };
 
-                        if new_time_after_pit < *best_times.get(&new_state).unwrap_or(&u32::MAX) {
+                        if let Some(new_time_after_pit) = new_time_after_pit
+                            && new_time_after_pit < *best_times.get(&new_state).unwrap_or(&Fixed::MAX)
+                        {
                             let mut new_path = current_path.clone();
                             new_path.push(neighbor_node);
                             let mut new_pit_stops = current_pit_stops.clone();
                             new_pit_stops.push((current_state.lap, current_state.current_node));
-                            min_heap.push(Label {
+                            let mut new_segments = current_segments.clone();
+                            new_segments.push(Segment {
+                                lap: current_state.lap,
+                                from: current_state.current_node,
+                                to: neighbor_node,
+                                pit_stop: true,
+                                distance: edge_distance,
+                                time: new_time_after_pit - current_time,
+                                cumulative_time: new_time_after_pit,
+                                fuel_before: current_state.current_fuel,
+                                fuel_after: new_fuel_after_pit_and_travel,
+                                tyre_before: current_state.tyre_distance,
+                                tyre_after: new_tyre_distance_after_pit_and_travel,
+                                tyre_penalty,
+                            });
+                            let new_label = Label {
                                 time: new_time_after_pit,
                                 state: new_state.clone(),
                                 path: new_path.clone(),
                                 pit_stops_taken: new_pit_stops.clone(),
-                            });
+                                segments: new_segments,
+                            };
+                            track_incumbent(&mut incumbent, &new_label, end_node, total_laps);
+                            min_heap.push(new_label);
+                            stats.labels_created += 1;
+                            stats.peak_heap = stats.peak_heap.max(min_heap.len());
                             // Re-enable for debugging
                             best_times.insert(new_state.clone(), new_time_after_pit);
                             best_paths.insert(new_state.clone(), new_path);
@@ -205,13 +621,38 @@ pub fn find_optimal_path(
         }
     }
 
-    if let (Some(path), Some(pit_stops)) = (overall_optimal_path, overall_optimal_pit_stops) {
-        Some(PathInfo {
+    let mut best = match (overall_optimal_path, overall_optimal_pit_stops, overall_optimal_segments) {
+        (Some(path), Some(pit_stops), Some(segments)) => Some(PathInfo {
             total_time: overall_min_time,
-            pit_stops: pit_stops,
+            pit_stops,
             node_sequence: path,
-        })
-    } else {
-        None
+            segments,
+        }),
+        _ => None,
+    };
+    if best.is_none()
+        && interrupted_at.is_some()
+        && let Some(label) = incumbent
+    {
+        overall_min_time = label.time;
+        best = Some(PathInfo { total_time: label.time, pit_stops: label.pit_stops_taken, node_sequence: label.path, segments: label.segments });
+    }
+    let (status, lower_bound) = match (interrupted_at, &best) {
+        (Some(frontier), Some(_)) => (SolveStatus::Feasible, Some(frontier.min(overall_min_time))),
+        (Some(frontier), None) => (SolveStatus::BudgetExceeded, Some(frontier)),
+        (None, Some(_)) => (SolveStatus::Optimal, Some(overall_min_time)),
+        (None, None) => (SolveStatus::Infeasible, None),
+    };
+    stats.elapsed = started.elapsed();
+
+    // Debug builds (and therefore tests) replay every strategy through the independent verifier
+    #[cfg(debug_assertions)]
+    if let Some(path_info) = &best
+        && let Err(discrepancies) = crate::verify::verify_strategy(race_data, graph, car_config, params, path_info)
+    {
+        let details: Vec<String> = discrepancies.iter().map(|d| d.to_string()).collect();
+        panic!("solver returned an invalid strategy: {}", details.join("; "));
     }
+
+    SolveOutcome { status, best, lower_bound, stats }
 }
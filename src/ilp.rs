@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use crate::graph_rcspp::{Graph, PathInfo, RaceParams};
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+
+// Mixed-integer model of the race in CPLEX LP format, for cross-checking the label-setting solver.
+//
+// The track is unrolled by lap into states (lap, node), following the solver's rule that entering
+// the start node begins a new lap. Variables, named after the lap and the edge or node index:
+//   x_L_E  binary, edge E is driven on lap L
+//   p_L_V  binary, the car pits at node V on lap L before driving on
+//   f_L_V  fuel on arrival at (L, V)
+//   w_L_V  tyre wear on arrival at (L, V)
+//   q_L_V  tyre wear beyond the threshold on arrival at (L, V)
+// Fuel and tyre wear are linked along driven edges with big-M constraints. Tyre wear since the last
+// pit stop never exceeds the fuel capacity, which keeps every big-M tight.
+//
+// The model assumes each (lap, node) state is visited at most once. The solver allows revisits
+// (e.g. a detour to a pit node and back), so the model's optimum can be slower on such tracks.
+// It always models the exact problem and ignores `params.resolution`.
+pub fn write_lp<W: Write>(race_data: &RaceData, car_config: &CarConfig, params: &RaceParams, out: &mut W) -> io::Result<()> {
+    let model = Model::new(race_data, params);
+    let capacity = car_config.fuel_capacity;
+    let source = (0, params.start_node);
+    let sink = (params.total_laps.saturating_sub(1), params.end_node);
+
+    writeln!(out, "\\ F1 race strategy: {} nodes, {} laps, fuel capacity {}, tyre cost {}", race_data.n, params.total_laps, capacity, car_config.tyre_cost)?;
+    writeln!(out, "Minimize")?;
+    let mut objective = Vec::new();
+    for arc in &model.arcs {
+        objective.push(term(arc.distance, &arc.var()));
+    }
+    for &(lap, node) in &model.pit_states {
+        objective.push(term(params.pit_stop_penalty, &var('p', lap, node)));
+    }
+    for &(lap, node) in &model.states {
+        if (lap, node) != source {
+            objective.push(term(car_config.tyre_cost, &var('q', lap, node)));
+        }
+    }
+    write_row(out, "time", &objective, "", None)?;
+
+    writeln!(out, "Subject To")?;
+    for &(lap, node) in &model.states {
+        let outgoing = &model.outgoing[&(lap, node)];
+        let incoming = &model.incoming[&(lap, node)];
+        let name = format!("{}_{}", lap, node);
+
+        // One unit of flow from the start state to the finish state
+        let mut flow: Vec<String> = outgoing.iter().map(|&a| format!("+ {}", model.arcs[a].var())).collect();
+        flow.extend(incoming.iter().map(|&a| format!("- {}", model.arcs[a].var())));
+        let supply = if source == sink { 0 } else if (lap, node) == source { 1 } else if (lap, node) == sink { -1 } else { 0 };
+        if !flow.is_empty() {
+            write_row(out, &format!("flow_{}", name), &flow, "=", Some(&supply.to_string()))?;
+        }
+
+        // Each state is entered at most once, and the start state never
+        if !incoming.is_empty() {
+            let entries: Vec<String> = incoming.iter().map(|&a| format!("+ {}", model.arcs[a].var())).collect();
+            let limit = if (lap, node) == source { "0" } else { "1" };
+            write_row(out, &format!("visit_{}", name), &entries, "<=", Some(limit))?;
+        }
+
+        // Pitting only makes sense when the car drives on
+        if model.is_pit(node) {
+            let mut pit = vec![format!("+ {}", var('p', lap, node))];
+            pit.extend(outgoing.iter().map(|&a| format!("- {}", model.arcs[a].var())));
+            write_row(out, &format!("pit_{}", name), &pit, "<=", Some("0"))?;
+        }
+
+        if (lap, node) != source {
+            let excess = [format!("+ {}", var('q', lap, node)), format!("- {}", var('w', lap, node))];
+            write_row(out, &format!("wear_{}", name), &excess, ">=", Some(&format!("-{}", params.tyre_wear_distance)))?;
+        }
+    }
+
+    for (a, arc) in model.arcs.iter().enumerate() {
+        let (from, to) = (arc.from, arc.to);
+        let d = arc.distance;
+        let x = arc.var();
+        let pit = model.is_pit(from.1).then(|| var('p', from.0, from.1));
+
+        // Arrival fuel is at most departure fuel minus the distance: f_to <= f_from + C p_from - d when driven
+        let mut fuel = vec![format!("+ {}", var('f', to.0, to.1)), format!("- {}", var('f', from.0, from.1))];
+        if let Some(p) = &pit {
+            fuel.push(format!("- {}", term(capacity, p)));
+        }
+        fuel.push(format!("+ {}", term(capacity + d, &x)));
+        write_row(out, &format!("fuel_{}", a), &fuel, "<=", Some(&capacity.to_string()))?;
+        // and never more than a full tank minus the distance
+        let tank = [format!("+ {}", var('f', to.0, to.1)), format!("+ {}", term(d, &x))];
+        write_row(out, &format!("tank_{}", a), &tank, "<=", Some(&capacity.to_string()))?;
+
+        // Tyre wear grows by the distance, restarting from zero after a pit stop
+        let mut tyre = vec![format!("+ {}", var('w', to.0, to.1)), format!("- {}", var('w', from.0, from.1))];
+        if let Some(p) = &pit {
+            tyre.push(format!("+ {}", term(capacity, p)));
+        }
+        tyre.push(format!("- {}", term(capacity + d, &x)));
+        write_row(out, &format!("tyre_{}", a), &tyre, ">=", Some(&format!("-{}", capacity)))?;
+        let fresh = [format!("+ {}", var('w', to.0, to.1)), format!("- {}", term(d, &x))];
+        write_row(out, &format!("fresh_{}", a), &fresh, ">=", Some("0"))?;
+    }
+
+    writeln!(out, "Bounds")?;
+    for &(lap, node) in &model.states {
+        if (lap, node) == source {
+            writeln!(out, " {} = {}", var('f', lap, node), capacity)?;
+            writeln!(out, " {} = 0", var('w', lap, node))?;
+        } else {
+            writeln!(out, " 0 <= {} <= {}", var('f', lap, node), capacity)?;
+            writeln!(out, " 0 <= {} <= {}", var('w', lap, node), capacity)?;
+        }
+    }
+
+    writeln!(out, "Binaries")?;
+    for arc in &model.arcs {
+        writeln!(out, " {}", arc.var())?;
+    }
+    for &(lap, node) in &model.pit_states {
+        writeln!(out, " {}", var('p', lap, node))?;
+    }
+    writeln!(out, "End")
+}
+
+// Why a solver's variable assignment could not be turned back into a strategy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    // No driven edge leaves this state although the finish has not been reached
+    DeadEnd { lap: u32, node: usize },
+    // More than one driven edge leaves this state
+    Branch { lap: u32, node: usize },
+    // The driven edges loop back to a state already on the path
+    Cycle { lap: u32, node: usize },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::DeadEnd { lap, node } => write!(f, "no driven edge leaves node {} on lap {}", node, lap),
+            ImportError::Branch { lap, node } => write!(f, "several driven edges leave node {} on lap {}", node, lap),
+            ImportError::Cycle { lap, node } => write!(f, "driven edges return to node {} on lap {}", node, lap),
+        }
+    }
+}
+
+// Rebuilds a strategy from a solver's solution to the model written by `write_lp`.
+// `solution` is any text listing variables with their values on the same line (`x_0_3 1`,
+// `x_0_3 = 1`, or solver reports with extra columns); only the `x_` and `p_` binaries are read.
+// The timeline and total time come from `verify::replay`, so they can be compared directly with
+// the label-setting solver's `PathInfo`.
+pub fn import_solution(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    solution: &str,
+) -> Result<PathInfo, ImportError> {
+    let model = Model::new(race_data, params);
+    let values = parse_values(solution);
+    let is_set = |name: &str| values.get(name).is_some_and(|&v| v > 0.5);
+
+    let source = (0, params.start_node);
+    let sink = (params.total_laps.saturating_sub(1), params.end_node);
+    let mut state = source;
+    let mut node_sequence = vec![params.start_node];
+    let mut pit_stops = Vec::new();
+    let mut visited = vec![source];
+
+    while state != sink {
+        let (lap, node) = state;
+        if model.is_pit(node) && is_set(&var('p', lap, node)) {
+            pit_stops.push(state);
+        }
+        let mut driven = model.outgoing.get(&state).into_iter().flatten().filter(|&&a| is_set(&model.arcs[a].var()));
+        let next = match (driven.next(), driven.next()) {
+            (Some(&a), None) => model.arcs[a].to,
+            (None, _) => return Err(ImportError::DeadEnd { lap, node }),
+            (Some(_), Some(_)) => return Err(ImportError::Branch { lap, node }),
+        };
+        if visited.contains(&next) {
+            return Err(ImportError::Cycle { lap: next.0, node: next.1 });
+        }
+        visited.push(next);
+        node_sequence.push(next.1);
+        state = next;
+    }
+
+    let (segments, _) = crate::verify::replay(race_data, graph, car_config, params, &node_sequence, &pit_stops);
+    Ok(PathInfo {
+        total_time: segments.last().map(|s| s.cumulative_time).unwrap_or(Fixed::ZERO),
+        pit_stops,
+        node_sequence,
+        segments,
+    })
+}
+
+// Variable values by name; the value is the first number after the name on its line
+fn parse_values(solution: &str) -> HashMap<String, f64> {
+    let mut values = HashMap::new();
+    for line in solution.lines() {
+        let mut tokens = line.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token.starts_with("x_") || token.starts_with("p_") {
+                if let Some(value) = tokens.by_ref().find_map(|t| t.parse::<f64>().ok()) {
+                    values.insert(token.to_string(), value);
+                }
+                break;
+            }
+        }
+    }
+    values
+}
+
+fn var(kind: char, lap: u32, index: usize) -> String {
+    format!("{}_{}_{}", kind, lap, index)
+}
+
+fn term(coefficient: Fixed, name: &str) -> String {
+    format!("{} {}", coefficient, name)
+}
+
+// Writes a row a few terms per line, keeping within the line length LP readers accept
+fn write_row<W: Write>(out: &mut W, name: &str, terms: &[String], sense: &str, rhs: Option<&str>) -> io::Result<()> {
+    write!(out, " {}:", name)?;
+    for (i, t) in terms.iter().enumerate() {
+        if i > 0 && i % 8 == 0 {
+            write!(out, "\n   ")?;
+        }
+        // Objective terms carry no sign of their own
+        if i > 0 && !t.starts_with('+') && !t.starts_with('-') {
+            write!(out, " +")?;
+        }
+        write!(out, " {}", t)?;
+    }
+    match rhs {
+        Some(rhs) => writeln!(out, " {} {}", sense, rhs),
+        None => writeln!(out),
+    }
+}
+
+// A driven edge on a given lap
+struct Arc {
+    edge: usize,
+    from: (u32, usize),
+    to: (u32, usize),
+    distance: Fixed,
+}
+
+impl Arc {
+    fn var(&self) -> String {
+        var('x', self.from.0, self.edge)
+    }
+}
+
+// The lap-expanded track shared by the exporter and the importer, so variable names always agree
+struct Model<'a> {
+    race_data: &'a RaceData,
+    states: Vec<(u32, usize)>,
+    pit_states: Vec<(u32, usize)>,
+    arcs: Vec<Arc>,
+    outgoing: HashMap<(u32, usize), Vec<usize>>,
+    incoming: HashMap<(u32, usize), Vec<usize>>,
+}
+
+impl<'a> Model<'a> {
+    fn new(race_data: &'a RaceData, params: &RaceParams) -> Self {
+        let sink = (params.total_laps.saturating_sub(1), params.end_node);
+        let mut model = Model {
+            race_data,
+            states: Vec::new(),
+            pit_states: Vec::new(),
+            arcs: Vec::new(),
+            outgoing: HashMap::new(),
+            incoming: HashMap::new(),
+        };
+        for lap in 0..params.total_laps {
+            for node in 0..race_data.n {
+                model.states.push((lap, node));
+                model.outgoing.insert((lap, node), Vec::new());
+                model.incoming.insert((lap, node), Vec::new());
+                if model.is_pit(node) {
+                    model.pit_states.push((lap, node));
+                }
+            }
+        }
+        for lap in 0..params.total_laps {
+            for (edge, e) in race_data.edges.iter().enumerate() {
+                let from = (lap, e.u);
+                // The race ends on arrival at the finish, so nothing leaves it
+                if from == sink || e.u >= race_data.n || e.v >= race_data.n {
+                    continue;
+                }
+                let next_lap = if e.v == params.start_node && e.u != params.start_node { lap + 1 } else { lap };
+                if next_lap >= params.total_laps {
+                    continue;
+                }
+                let to = (next_lap, e.v);
+                model.outgoing.entry(from).or_default().push(model.arcs.len());
+                model.incoming.entry(to).or_default().push(model.arcs.len());
+                model.arcs.push(Arc { edge, from, to, distance: e.distance });
+            }
+        }
+        model
+    }
+
+    fn is_pit(&self, node: usize) -> bool {
+        self.race_data.pit_nodes.contains(&node)
+    }
+}
@@ -1,18 +1,28 @@
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::units::Fixed;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CarConfig {
-    pub fuel_capacity: u32,
-    pub tyre_cost: u32,
+    pub fuel_capacity: Fixed,
+    pub tyre_cost: Fixed,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edge {
     pub u: usize,
     pub v: usize,
-    pub distance: u32,
+    pub distance: Fixed,
 }
 
+// Where a node sits on the circuit map, in any consistent length unit with y pointing up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+#[derive(Debug, Clone)]
 pub struct RaceData {
     pub n: usize,
     pub np: usize,
@@ -21,49 +31,208 @@ pub struct RaceData {
     pub edges: Vec<Edge>,
     pub pit_nodes: Vec<usize>,
     pub cars: Vec<CarConfig>,
+    // One per node when the track has a physical layout
+    pub positions: Option<Vec<Position>>,
+}
+
+impl RaceData {
+    // Each node's outgoing edges as (to, distance), in input order
+    pub fn adjacency(&self) -> Vec<Vec<(usize, Fixed)>> {
+        let mut adj = vec![Vec::new(); self.n];
+        for edge in &self.edges {
+            adj[edge.u].push((edge.v, edge.distance));
+        }
+        adj
+    }
 }
 
+// Largest track accepted, so a hostile node count cannot exhaust memory
+pub const MAX_NODES: usize = 1 << 20;
+
+// Largest distance, fuel capacity or tyre cost accepted. Tyre wear never exceeds the fuel
+// capacity, so a segment's tyre penalty stays far inside `Fixed`'s range.
+pub const MAX_VALUE: Fixed = Fixed::from_int(1_000_000);
+
+// The structured track format: the same data as the text format, without the counts
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrackFile {
+    nodes: usize,
+    pit_nodes: Vec<usize>,
+    edges: Vec<Edge>,
+    cars: Vec<CarConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    positions: Option<Vec<Position>>,
+}
+
+// Reads a track file, choosing the format from the extension (`.json`, otherwise text)
 pub fn parse_input(file_path: &str) -> io::Result<RaceData> {
-    let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
+    let text = std::fs::read_to_string(file_path)?;
+    if file_path.ends_with(".json") { parse_json_str(&text) } else { parse_str(&text) }
+}
+
+// Parses the JSON format; anything returned has passed `validate`
+pub fn parse_json_str(text: &str) -> io::Result<RaceData> {
+    let track: TrackFile = serde_json::from_str(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let race_data = RaceData {
+        n: track.nodes,
+        np: track.pit_nodes.len(),
+        c: track.cars.len(),
+        m: track.edges.len(),
+        edges: track.edges,
+        pit_nodes: track.pit_nodes,
+        cars: track.cars,
+        positions: track.positions,
+    };
+    validate(&race_data)?;
+    Ok(race_data)
+}
+
+// Line opening the node positions in the text format
+const POSITIONS_HEADER: &str = "positions";
+
+// The track in the text format read by `parse_str`
+pub fn to_text(race_data: &RaceData) -> String {
+    let mut text = format!("{}\n{}\n{}\n{}\n", race_data.n, race_data.pit_nodes.len(), race_data.cars.len(), race_data.edges.len());
+    for e in &race_data.edges {
+        text.push_str(&format!("{} {} {}\n", e.u, e.v, e.distance));
+    }
+    for pit_node in &race_data.pit_nodes {
+        text.push_str(&format!("{}\n", pit_node));
+    }
+    for car in &race_data.cars {
+        text.push_str(&format!("{} {}\n", car.fuel_capacity, car.tyre_cost));
+    }
+    if let Some(positions) = &race_data.positions {
+        text.push_str(POSITIONS_HEADER);
+        text.push('\n');
+        for p in positions {
+            text.push_str(&format!("{} {}\n", p.x, p.y));
+        }
+    }
+    text
+}
 
-    let n: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing N")))??.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid N")))?;
-    let np: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing NP")))??.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid NP")))?;
-    let c: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing C")))??.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid C")))?;
-    let m: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing M")))??.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid M")))?;
+// The track in the JSON format read by `parse_json_str`
+pub fn to_json(race_data: &RaceData) -> String {
+    let track = TrackFile {
+        nodes: race_data.n,
+        pit_nodes: race_data.pit_nodes.clone(),
+        edges: race_data.edges.clone(),
+        cars: race_data.cars.clone(),
+        positions: race_data.positions.clone(),
+    };
+    let mut json = serde_json::to_string_pretty(&track).expect("tracks always serialise");
+    json.push('\n');
+    json
+}
+
+// Parses the text format; anything returned has passed `validate`
+pub fn parse_str(text: &str) -> io::Result<RaceData> {
+    let mut lines = text.lines();
+
+    let n: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing N")))?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid N")))?;
+    let np: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing NP")))?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid NP")))?;
+    let c: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing C")))?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid C")))?;
+    let m: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, String::from("Missing M")))?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, String::from("Invalid M")))?;
 
-    let mut edges = Vec::with_capacity(m);
+    let mut edges = Vec::new();
     for i in 0..m {
-        let line = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing edge line {}", i)))??;
-        let parts: Vec<&str> = line.trim().split_whitespace().collect();
+        let line = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing edge line {}", i)))?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() != 3 {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid edge format on line {}", i)));
         }
         let u: usize = parts[0].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid U on line {}", i)))?;
         let v: usize = parts[1].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid V on line {}", i)))?;
-        let distance: u32 = parts[2].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Distance on line {}", i)))?;
+        let distance: Fixed = parts[2].parse().ok().filter(|d| *d >= Fixed::ZERO).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Distance on line {}", i)))?;
         edges.push(Edge { u, v, distance });
     }
 
-    let mut pit_nodes = Vec::with_capacity(np);
+    let mut pit_nodes = Vec::new();
     for i in 0..np {
-        let pit_node: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing pit node line {}", i)))??.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Pit Node on line {}", i)))?;
+        let pit_node: usize = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing pit node line {}", i)))?.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Pit Node on line {}", i)))?;
         pit_nodes.push(pit_node);
     }
 
-    let mut cars = Vec::with_capacity(c);
+    let mut cars = Vec::new();
     for i in 0..c {
-        let line = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing car config line {}", i)))??;
-        let parts: Vec<&str> = line.trim().split_whitespace().collect();
+        let line = lines.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing car config line {}", i)))?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() != 2 {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid car config format on line {}", i)));
         }
-        let fuel_capacity: u32 = parts[0].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Fuel Capacity on line {}", i)))?;
-        let tyre_cost: u32 = parts[1].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Tyre Cost on line {}", i)))?;
+        let fuel_capacity: Fixed = parts[0].parse().ok().filter(|f| *f >= Fixed::ZERO).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Fuel Capacity on line {}", i)))?;
+        let tyre_cost: Fixed = parts[1].parse().ok().filter(|t| *t >= Fixed::ZERO).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Tyre Cost on line {}", i)))?;
         cars.push(CarConfig { fuel_capacity, tyre_cost });
     }
 
-    Ok(RaceData { n, np, c, m, edges, pit_nodes, cars })
+    // Optional trailing section: a `positions` line, then one `X Y` line per node. Any other text
+    // after the cars is ignored, so files can end with notes.
+    let mut positions = None;
+    let mut rest = lines.filter(|line| !line.trim().is_empty()).peekable();
+    if rest.next_if(|line| line.trim() == POSITIONS_HEADER).is_some() {
+        let mut points = Vec::new();
+        for i in 0..n.min(MAX_NODES) {
+            let line = rest.next().ok_or(io::Error::new(io::ErrorKind::InvalidInput, format!("Missing position line {}", i)))?;
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 2 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid position format on line {}", i)));
+            }
+            let x: Fixed = parts[0].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid X on line {}", i)))?;
+            let y: Fixed = parts[1].parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid Y on line {}", i)))?;
+            points.push(Position { x, y });
+        }
+        if rest.next().is_some() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("More than {} position lines", n)));
+        }
+        positions = Some(points);
+    }
+
+    let race_data = RaceData { n, np, c, m, edges, pit_nodes, cars, positions };
+    validate(&race_data)?;
+    Ok(race_data)
+}
+
+// Checks that counts match the lists, every edge and pit node refers to a node on the track,
+// distances, fuel capacities and tyre costs lie within 0..=`MAX_VALUE`, and positions, if any,
+// cover every node
+pub fn validate(race_data: &RaceData) -> io::Result<()> {
+    let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    if race_data.n > MAX_NODES {
+        return invalid(format!("Track has {} nodes, more than the limit of {}", race_data.n, MAX_NODES));
+    }
+    if race_data.m != race_data.edges.len() || race_data.np != race_data.pit_nodes.len() || race_data.c != race_data.cars.len() {
+        return invalid(String::from("Counts do not match the number of edges, pit nodes or cars"));
+    }
+    for (i, e) in race_data.edges.iter().enumerate() {
+        if e.u >= race_data.n || e.v >= race_data.n {
+            return invalid(format!("Edge {} ({} -> {}) refers to a node outside 0..{}", i, e.u, e.v, race_data.n));
+        }
+        if e.distance < Fixed::ZERO {
+            return invalid(format!("Edge {} has a negative distance", i));
+        }
+        if e.distance > MAX_VALUE {
+            return invalid(format!("Edge {} has a distance above the limit of {}", i, MAX_VALUE));
+        }
+    }
+    for &pit_node in &race_data.pit_nodes {
+        if pit_node >= race_data.n {
+            return invalid(format!("Pit node {} is outside 0..{}", pit_node, race_data.n));
+        }
+    }
+    for (i, car) in race_data.cars.iter().enumerate() {
+        if car.fuel_capacity < Fixed::ZERO || car.tyre_cost < Fixed::ZERO {
+            return invalid(format!("Car {} has a negative fuel capacity or tyre cost", i));
+        }
+        if car.fuel_capacity > MAX_VALUE || car.tyre_cost > MAX_VALUE {
+            return invalid(format!("Car {} has a fuel capacity or tyre cost above the limit of {}", i, MAX_VALUE));
+        }
+    }
+    if let Some(positions) = &race_data.positions
+        && positions.len() != race_data.n
+    {
+        return invalid(format!("Track has {} positions for {} nodes", positions.len(), race_data.n));
+    }
+    Ok(())
 }
@@ -0,0 +1,55 @@
+use serde::Serialize;
+use crate::graph_rcspp::{PathInfo, SolveError};
+use crate::input_parser::{CarConfig, Edge, Position, RaceData};
+
+#[derive(Serialize)]
+struct Report<'a> {
+    track: Track<'a>,
+    cars: Vec<CarResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct Track<'a> {
+    nodes: usize,
+    pit_nodes: &'a [usize],
+    edges: &'a [Edge],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    positions: Option<&'a [Position]>,
+}
+
+// One car's strategy, or why it has none
+#[derive(Serialize)]
+struct CarResult<'a> {
+    car: usize,
+    #[serde(flatten)]
+    config: &'a CarConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strategy: Option<&'a PathInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// Machine-readable results: the track and, per solved car, its strategy with the full timeline.
+// Each result comes with the car's 0-based index in `race_data.cars`, and is numbered from 1 by it.
+pub fn results_json(race_data: &RaceData, results: &[(usize, Result<PathInfo, SolveError>)]) -> String {
+    let report = Report {
+        track: Track {
+            nodes: race_data.n,
+            pit_nodes: &race_data.pit_nodes,
+            edges: &race_data.edges,
+            positions: race_data.positions.as_deref(),
+        },
+        cars: results
+            .iter()
+            .map(|(i, result)| CarResult {
+                car: i + 1,
+                config: &race_data.cars[*i],
+                strategy: result.as_ref().ok(),
+                error: result.as_ref().err().map(|e| e.to_string()),
+            })
+            .collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&report).expect("race results always serialise");
+    json.push('\n');
+    json
+}
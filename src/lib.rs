@@ -0,0 +1,18 @@
+pub mod input_parser;
+pub mod graph_rcspp;
+pub mod bidirectional;
+pub mod charts;
+pub mod comparison;
+pub mod diagnosis;
+pub mod generator;
+pub mod ilp;
+pub mod json;
+pub mod output;
+pub mod pareto;
+pub mod render;
+pub mod report;
+pub mod track_map;
+pub mod tui;
+pub mod verify;
+pub mod visual;
+pub mod units;
@@ -1,148 +1,883 @@
 use colored::*;
+use std::process::ExitCode;
 use std::thread;
 use std::time::Duration;
-pub mod input_parser;
-pub mod graph_rcspp;
-pub mod visual;
+use f1_track::{diagnosis, generator, graph_rcspp, ilp, input_parser, json, output, pareto, report, tui, verify, visual};
+use f1_track::comparison::compare_strategies;
+use f1_track::graph_rcspp::{Graph, PathInfo, RaceParams, Resolution, SolveError, SolveOptions, SolveOutcome, SolveStatus};
+use f1_track::input_parser::{CarConfig, RaceData};
+use f1_track::output::{OutputOptions, RunDir};
+use f1_track::render::{self, Engine, ImageFormat};
+use f1_track::visual::car_color;
+use f1_track::units::Fixed;
 
-fn main() {
-    println!("F1 Track Optimization System");
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <input_file_path>", args[0]);
-        std::process::exit(1);
+// Process exit codes, ordered by severity so the worst outcome across cars wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Exit {
+    Ok = 0,
+    // A car has no feasible strategy, or `validate` found a track that cannot be raced
+    Infeasible = 1,
+    // The command line could not be understood
+    Usage = 2,
+    // The track file could not be read or is malformed
+    Input = 3,
+    // A solve budget ran out before a strategy was proven optimal
+    Budget = 4,
+    // An output file could not be written
+    Output = 5,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
     }
-    let input_file_path = &args[1];
+}
 
-    match input_parser::parse_input(input_file_path) {
-        Ok(race_data) => {
-            println!("Successfully parsed input:");
-            println!("Nodes: {}, Edges: {}, Pit Nodes: {}, Cars: {}", race_data.n, race_data.m, race_data.pit_nodes.len(), race_data.cars.len());
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Validate,
+    Solve,
+    Render,
+    Replay,
+    Compare,
+    Tui,
+}
 
-            // Print graph visualization and export DOT (graphviz)
-            visual::print_graph_visualization(&race_data);
-            if let Err(e) = visual::export_dot(&race_data, "track.dot") {
-                eprintln!("Failed to export DOT: {}", e);
-            } else {
-                println!("Exported track graph DOT to {} (render with: dot -Tsvg track.dot -o track.svg)", "track.dot".green());
-            }
+impl Command {
+    fn parse(name: &str) -> Option<Command> {
+        match name {
+            "validate" => Some(Command::Validate),
+            "solve" => Some(Command::Solve),
+            "render" => Some(Command::Render),
+            "replay" => Some(Command::Replay),
+            "compare" => Some(Command::Compare),
+            "tui" => Some(Command::Tui),
+            _ => None,
+        }
+    }
+
+    // Flags this command accepts on top of the shared ones
+    fn extra_flags(self) -> &'static [&'static str] {
+        match self {
+            Command::Solve => &["--report-gap", "--pareto", "--map", "--export-lp", "--lp-solutions", "--json", "--out", "--run-name", "--overwrite"],
+            Command::Render => &["--out", "--run-name", "--overwrite", "--image", "--renderer"],
+            Command::Replay | Command::Tui => &["--delay"],
+            Command::Validate | Command::Compare => &[],
+        }
+    }
+}
 
-            let graph = graph_rcspp::Graph::new(race_data.n, &race_data.edges);
-            println!("\nGraph created with {} nodes and {} edges.", race_data.n, race_data.m);
+// Flags that only some commands accept
+const COMMAND_FLAGS: &[&str] = &[
+    "--report-gap", "--pareto", "--map", "--export-lp", "--lp-solutions", "--json", "--delay", "--out", "--run-name", "--overwrite",
+    "--image", "--renderer",
+];
 
-            if race_data.cars.is_empty() {
-                eprintln!("Error: No car configurations provided in the input.");
-                std::process::exit(1);
-            }
-            
-            for (i, car_config) in race_data.cars.iter().enumerate() {
-                let car_color = match i % 3 {
-                    0 => Color::Green,
-                    1 => Color::Blue,
-                    _ => Color::Magenta,
-                };
+// How fuel and tyre wear are discretised, as chosen on the command line
+enum ResolutionOption {
+    Steps { fuel: Fixed, tyre: Fixed },
+    Buckets(u32),
+}
 
-                println!("\n--- Processing Car Configuration {} ---", (i + 1).to_string().color(car_color));
-                println!("  Fuel Capacity: {}, Tyre Cost: {}", car_config.fuel_capacity.to_string().color(car_color), car_config.tyre_cost.to_string().color(car_color));
-
-                let start_node = 0;
-                let end_node = race_data.n - 1;
-                let total_laps = 1; // Default to 1 lap as K is not in Section 4.2 input
-                let pit_stop_penalty = 60; // Realistic pit stop penalty
-                let tyre_wear_distance = 100; // Realistic tyre wear distance
-
-                match graph_rcspp::find_optimal_path(
-                    &race_data,
-                    &graph,
-                    start_node,
-                    end_node,
-                    total_laps,
-                    car_config,
-                    pit_stop_penalty,
-                    tyre_wear_distance,
-                ) {
-                    Some(path_info) => {
-                        // Print race strategy visualization
-                        visual::print_race_strategy(i, &path_info, &race_data);
-                        // Export per-lap highlighted DOTs to images/
-                        let prefix = format!("car{}_laps", i + 1);
-                        if let Err(e) = visual::export_highlighted_dots(&race_data, &path_info, start_node, total_laps, &prefix) {
-                            eprintln!("Failed to export per-lap DOTs for car {}: {}", i + 1, e);
-                        } else {
-                            println!("Saved per-lap DOTs under {}", "images/".green());
-                            println!("Render with: dot -Tsvg images/{}_lap1.dot -o images/{}_lap1.svg", prefix, prefix);
-                        }
-                        
-                        println!("\n--- Visualizing Car {}'s Journey ---", (i + 1).to_string().color(car_color));
-                        let mut current_fuel = car_config.fuel_capacity;
-                        let mut current_tyre_distance = 0;
-                        let mut current_lap = 0; // Assuming starting lap 0
-
-                        for (idx, &node) in path_info.node_sequence.iter().enumerate() {
-                            let is_pit_stop_node = race_data.pit_nodes.contains(&node);
-                            let mut performed_pit_stop_this_step = false;
-
-                            if is_pit_stop_node && idx > 0 { // Check if a pit stop occurred at this node
-                                // Check if this node was actually part of the recorded pit stops
-                                if path_info.pit_stops.iter().any(|&(_lap, p_node)| p_node == node) {
-                                    println!("  {} at Node {}. Refueling and changing tires.", "PIT STOP!".color(Color::Red), node.to_string().color(Color::Red));
-                                    current_fuel = car_config.fuel_capacity; // Refuel
-                                    current_tyre_distance = 0; // Reset tyre wear
-                                    performed_pit_stop_this_step = true;
-                                    thread::sleep(Duration::from_millis(500)); // Short pause for pit stop
-                                }
-                            }
+// Everything a track command was asked to do
+struct Args {
+    input: String,
+    start: usize,
+    end: Option<usize>,
+    laps: u32,
+    pit_penalty: Fixed,
+    tyre_wear: Fixed,
+    resolution: ResolutionOption,
+    options: SolveOptions,
+    // 1-based car numbers to run; every car when empty
+    cars: Vec<usize>,
+    report_gap: bool,
+    pareto: bool,
+    // Draw each strategy on the terminal track map
+    map: bool,
+    export_lp: bool,
+    lp_solutions: Option<String>,
+    json: Option<String>,
+    delay: Duration,
+    output: OutputOptions,
+    // Image drawn next to every DOT `render` writes; none when `None`
+    image: Option<ImageFormat>,
+    renderer: Engine,
+}
 
-                            if idx > 0 {
-                                // Simulate fuel and tyre usage for the segment just completed
-                                // This part is tricky as optimal_path doesn't store per-segment fuel/tyre usage history
-                                // For visualization, we'll re-calculate based on previous node to current node
-                                let prev_node = path_info.node_sequence[idx - 1];
-                                if let Some(neighbors) = graph.adj.get(&prev_node) {
-                                    for &(neighbor_node, edge_distance) in neighbors {
-                                        if neighbor_node == node {
-                                            if !performed_pit_stop_this_step {
-                                                current_fuel = current_fuel.saturating_sub(edge_distance);
-                                                current_tyre_distance += edge_distance;
-                                            }
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
+fn usage(program: &str) {
+    eprintln!("Usage: {} <command> <input_file_path> [flags]", program);
+    eprintln!();
+    eprintln!("Commands:");
+    eprintln!("  validate   check that the track parses and every car can finish the race");
+    eprintln!("  solve      find and print each car's optimal strategy");
+    eprintln!("  render     print the track and write its DOTs, images, circuit maps and an HTML report");
+    eprintln!("  replay     animate each car's strategy node by node");
+    eprintln!("  compare    rank the cars' strategies side by side");
+    eprintln!("  tui        step through the strategies full-screen, changing the race and re-solving");
+    eprintln!("  generate   write a random track (takes no input file)");
+    eprintln!();
+    eprintln!("Shared flags:");
+    eprintln!("  --start NODE  --end NODE  --laps N  --pit-penalty T  --tyre-wear D  --cars N[,N...]");
+    eprintln!("  --fuel-resolution STEP  --tyre-resolution STEP  --buckets N");
+    eprintln!("  --timeout SECONDS  --max-labels N  --max-heap N  --no-color");
+    eprintln!("solve: [--report-gap] [--pareto] [--map] [--export-lp] [--lp-solutions DIR] [--json FILE]");
+    eprintln!("solve, render: [--out DIR] [--run-name NAME] [--overwrite error|replace|increment]");
+    eprintln!("render: [--image svg|png|none] [--renderer auto|graphviz|builtin]");
+    eprintln!("replay, tui: [--delay MS]");
+    eprintln!("generate: [--nodes N] [--shape ring|grid|circuit] [--shortcuts N] [--pits N]");
+    eprintln!("          [--pit-placement spaced|random|lane] [--min-distance D] [--max-distance D] [--car FUEL,TYRE_COST]...");
+    eprintln!("          [--seed N] [--format text|json] [--output FILE]");
+    eprintln!();
+    eprintln!("Exit codes: 0 ok, 1 no feasible strategy, 2 usage, 3 bad input, 4 budget exceeded, 5 output failed");
+}
 
-                            let node_display = if is_pit_stop_node {
-                                node.to_string().color(Color::Red).to_string()
-                            } else {
-                                node.to_string().color(car_color).to_string()
-                            };
-
-                            println!("  Car {} at Node {}. Fuel: {}, Tyre Distance: {}", 
-                                (i + 1).to_string().color(car_color),
-                                node_display,
-                                current_fuel.to_string().color(Color::Cyan),
-                                current_tyre_distance.to_string().color(Color::Yellow)
-                            );
-
-                            if node == start_node && idx > 0 { // Increment lap if returned to start node
-                                current_lap += 1;
-                                println!("  --- Entering Lap {} ---", (current_lap + 1).to_string().color(Color::White));
-                            }
+// Parses the value following `flag`
+fn value<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
 
-                            thread::sleep(Duration::from_millis(700)); // Pause for readability
-                        }
-                        println!("--- Journey Complete for Car {} ---", (i + 1).to_string().color(car_color));
-                        
-                    },
-                    None => {
-                        println!("No feasible race strategy found for this car configuration.");
+fn parse_args(command: Command, args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        input: String::new(),
+        start: 0,
+        end: None,
+        laps: 1,
+        pit_penalty: Fixed::from_int(60), // Realistic pit stop penalty
+        tyre_wear: Fixed::from_int(100), // Realistic tyre wear distance
+        resolution: ResolutionOption::Steps { fuel: Fixed::EPSILON, tyre: Fixed::EPSILON },
+        options: SolveOptions::default(),
+        cars: Vec::new(),
+        report_gap: false,
+        pareto: false,
+        map: false,
+        export_lp: false,
+        lp_solutions: None,
+        json: None,
+        delay: Duration::from_millis(700),
+        output: OutputOptions::default(),
+        image: Some(ImageFormat::Svg),
+        renderer: Engine::Auto,
+    };
+    let mut input = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let flag = arg.as_str();
+        if COMMAND_FLAGS.contains(&flag) && !command.extra_flags().contains(&flag) {
+            return Err(format!("{} is not a flag of this command", flag));
+        }
+        match flag {
+            "--start" => parsed.start = value(flag, rest.next())?,
+            "--end" => parsed.end = Some(value(flag, rest.next())?),
+            "--laps" => match value(flag, rest.next())? {
+                0 => return Err("--laps must be at least 1".to_string()),
+                laps => parsed.laps = laps,
+            },
+            "--pit-penalty" | "--tyre-wear" => {
+                let amount: Fixed = value(flag, rest.next())?;
+                if amount < Fixed::ZERO {
+                    return Err(format!("{} must not be negative", flag));
+                }
+                if flag == "--pit-penalty" {
+                    parsed.pit_penalty = amount;
+                } else {
+                    parsed.tyre_wear = amount;
+                }
+            }
+            "--cars" => {
+                let list: String = value(flag, rest.next())?;
+                for car in list.split(',') {
+                    match car.trim().parse() {
+                        Ok(car) if car > 0 => parsed.cars.push(car),
+                        _ => return Err(format!("invalid car number '{}' for --cars", car)),
                     }
                 }
             }
+            "--fuel-resolution" | "--tyre-resolution" => {
+                let step: Fixed = value(flag, rest.next())?;
+                if !step.is_positive() {
+                    return Err(format!("{} must be positive", flag));
+                }
+                let (fuel, tyre) = match parsed.resolution {
+                    ResolutionOption::Steps { fuel, tyre } => (fuel, tyre),
+                    ResolutionOption::Buckets(_) => (Fixed::EPSILON, Fixed::EPSILON),
+                };
+                parsed.resolution = if flag == "--fuel-resolution" {
+                    ResolutionOption::Steps { fuel: step, tyre }
+                } else {
+                    ResolutionOption::Steps { fuel, tyre: step }
+                };
+            }
+            "--buckets" => match value(flag, rest.next())? {
+                0 => return Err("--buckets must be at least 1".to_string()),
+                buckets => parsed.resolution = ResolutionOption::Buckets(buckets),
+            },
+            "--timeout" => match value::<f64>(flag, rest.next())? {
+                seconds if seconds >= 0.0 && seconds.is_finite() => parsed.options.timeout = Some(Duration::from_secs_f64(seconds)),
+                _ => return Err("--timeout must be a non-negative number of seconds".to_string()),
+            },
+            "--max-labels" => parsed.options.max_labels = Some(value(flag, rest.next())?),
+            "--max-heap" => parsed.options.max_heap = Some(value(flag, rest.next())?),
+            "--no-color" => colored::control::set_override(false),
+            "--report-gap" => parsed.report_gap = true,
+            "--pareto" => parsed.pareto = true,
+            "--map" => parsed.map = true,
+            "--export-lp" => parsed.export_lp = true,
+            "--lp-solutions" => parsed.lp_solutions = Some(value(flag, rest.next())?),
+            "--json" => {
+                let name: String = value(flag, rest.next())?;
+                if std::path::Path::new(&name).file_name() != Some(name.as_ref()) {
+                    return Err(format!("--json takes a file name for the run folder, not the path '{}'", name));
+                }
+                parsed.json = Some(name);
+            }
+            "--delay" => parsed.delay = Duration::from_millis(value(flag, rest.next())?),
+            "--out" => parsed.output.root = value::<String>(flag, rest.next())?.into(),
+            "--run-name" => parsed.output.run_name = Some(value(flag, rest.next())?),
+            "--overwrite" => parsed.output.overwrite = rest.next().ok_or("--overwrite needs a value")?.parse()?,
+            "--image" => match rest.next().ok_or("--image needs a value")?.as_str() {
+                "none" => parsed.image = None,
+                format => parsed.image = Some(format.parse()?),
+            },
+            "--renderer" => parsed.renderer = rest.next().ok_or("--renderer needs a value")?.parse()?,
+            _ if input.is_none() && !flag.starts_with("--") => input = Some(arg.clone()),
+            _ if flag.starts_with("--") => return Err(format!("unknown flag {}", flag)),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+    parsed.input = input.ok_or("missing input file path")?;
+    Ok(parsed)
+}
+
+// `generate`: writes a random track built from the flags, to `--output` or stdout
+fn generate(args: &[String]) -> Result<Exit, String> {
+    let mut spec = generator::TrackSpec::default();
+    let mut cars = Vec::new();
+    let mut format = None;
+    let mut output: Option<String> = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let flag = arg.as_str();
+        match flag {
+            "--nodes" => spec.nodes = value(flag, rest.next())?,
+            "--shape" => spec.shape = rest.next().ok_or("--shape needs a value")?.parse()?,
+            "--shortcuts" => spec.shortcuts = value(flag, rest.next())?,
+            "--pits" => spec.pits = value(flag, rest.next())?,
+            "--pit-placement" => spec.pit_placement = rest.next().ok_or("--pit-placement needs a value")?.parse()?,
+            "--min-distance" => spec.min_distance = value(flag, rest.next())?,
+            "--max-distance" => spec.max_distance = value(flag, rest.next())?,
+            "--seed" => spec.seed = value(flag, rest.next())?,
+            "--car" => {
+                let car: String = value(flag, rest.next())?;
+                match car.split_once(',').map(|(f, t)| (f.parse::<Fixed>(), t.parse::<Fixed>())) {
+                    Some((Ok(fuel_capacity), Ok(tyre_cost))) => cars.push(CarConfig { fuel_capacity, tyre_cost }),
+                    _ => return Err(format!("invalid car '{}' (expected FUEL,TYRE_COST)", car)),
+                }
+            }
+            "--format" => match rest.next().map(String::as_str) {
+                Some(f @ ("text" | "json")) => format = Some(f.to_string()),
+                _ => return Err("--format must be text or json".to_string()),
+            },
+            "--output" => output = Some(value(flag, rest.next())?),
+            "--no-color" => colored::control::set_override(false),
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    if !cars.is_empty() {
+        spec.cars = cars;
+    }
+    if spec.nodes < 2 || spec.min_distance > spec.max_distance || !spec.min_distance.is_positive() {
+        return Err("need at least 2 nodes and 0 < min distance <= max distance".to_string());
+    }
+
+    generator::check_spec(&spec).map_err(|e| e.to_string())?;
+    let race_data = generator::generate(&spec).map_err(|e| e.to_string())?;
+    let json = format.as_deref() == Some("json") || (format.is_none() && output.as_deref().is_some_and(|o| o.ends_with(".json")));
+    let text = if json { input_parser::to_json(&race_data) } else { input_parser::to_text(&race_data) };
+    match output {
+        Some(path) => match std::fs::write(&path, text) {
+            Ok(()) => eprintln!("Wrote {}-node track to {}", race_data.n, path.green()),
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", path, e);
+                return Ok(Exit::Output);
+            }
         },
+        None => print!("{}", text),
+    }
+    Ok(Exit::Ok)
+}
+
+// A parsed track together with its graph and the 0-based indices of the cars to run
+struct Race {
+    race_data: RaceData,
+    graph: Graph,
+    cars: Vec<usize>,
+}
+
+// Reads the input file and checks the shared flags against it
+fn load(args: &Args) -> Result<Race, Exit> {
+    let race_data = match input_parser::parse_input(&args.input) {
+        Ok(race_data) => race_data,
         Err(e) => {
             eprintln!("Error parsing input: {}", e);
-            std::process::exit(1);
+            return Err(Exit::Input);
+        }
+    };
+    for (flag, node) in [("--start", args.start), ("--end", args.end.unwrap_or(0))] {
+        if node >= race_data.n {
+            eprintln!("Error: {} {} is not a node of this {}-node track", flag, node, race_data.n);
+            return Err(Exit::Usage);
+        }
+    }
+    if let Some(&car) = args.cars.iter().find(|&&car| car > race_data.cars.len()) {
+        eprintln!("Error: --cars {} but the track only has {} car(s)", car, race_data.cars.len());
+        return Err(Exit::Usage);
+    }
+    let cars = if args.cars.is_empty() { (0..race_data.cars.len()).collect() } else { args.cars.iter().map(|car| car - 1).collect() };
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    Ok(Race { race_data, graph, cars })
+}
+
+fn params_for(args: &Args, race_data: &RaceData, car_config: &CarConfig) -> RaceParams {
+    RaceParams {
+        start_node: args.start,
+        end_node: args.end.unwrap_or(race_data.n - 1),
+        total_laps: args.laps,
+        pit_stop_penalty: args.pit_penalty,
+        tyre_wear_distance: args.tyre_wear,
+        resolution: match args.resolution {
+            ResolutionOption::Steps { fuel, tyre } => Resolution { fuel, tyre },
+            ResolutionOption::Buckets(buckets) => Resolution::buckets(car_config, buckets),
+        },
+    }
+}
+
+// Solves the selected cars in parallel, returning each car's index and outcome in input order
+fn solve_cars(args: &Args, race: &Race) -> Vec<(usize, Result<SolveOutcome, SolveError>)> {
+    let selected = RaceData {
+        c: race.cars.len(),
+        cars: race.cars.iter().map(|&i| race.race_data.cars[i].clone()).collect(),
+        ..race.race_data.clone()
+    };
+    let results = graph_rcspp::solve_all_with(&selected, &race.graph, &args.options, |car| params_for(args, &race.race_data, car));
+    race.cars.iter().copied().zip(results).collect()
+}
+
+fn exit_for(result: &Result<SolveOutcome, SolveError>) -> Exit {
+    match result {
+        Ok(outcome) => match outcome.status {
+            SolveStatus::Optimal => Exit::Ok,
+            SolveStatus::Feasible | SolveStatus::BudgetExceeded => Exit::Budget,
+            SolveStatus::Infeasible => Exit::Infeasible,
+        },
+        Err(SolveError::BudgetExceeded) => Exit::Budget,
+        Err(_) => Exit::Infeasible,
+    }
+}
+
+fn print_car_header(i: usize, car_config: &CarConfig) {
+    let color = car_color(i);
+    println!("\n--- Processing Car Configuration {} ---", (i + 1).to_string().color(color));
+    println!("  Fuel Capacity: {}, Tyre Cost: {}", car_config.fuel_capacity.to_string().color(color), car_config.tyre_cost.to_string().color(color));
+}
+
+fn print_summary(race_data: &RaceData) {
+    println!("Successfully parsed input:");
+    println!("Nodes: {}, Edges: {}, Pit Nodes: {}, Cars: {}", race_data.n, race_data.m, race_data.pit_nodes.len(), race_data.cars.len());
+}
+
+// Creates this run's output folder, reporting where artefacts will go
+fn create_run(args: &Args, command: &str) -> Option<RunDir> {
+    match RunDir::create(&args.output, &args.input, command) {
+        Ok(run) => {
+            println!("Writing artefacts to {}", run.path().display().to_string().green());
+            Some(run)
+        }
+        Err(e) => {
+            eprintln!("Failed to create output folder: {}", e);
+            None
+        }
+    }
+}
+
+// Writes one artefact into the run folder, reporting a failure rather than stopping the run
+fn save(run: &mut RunDir, name: &str, contents: impl AsRef<[u8]>, kind: &'static str, car: Option<usize>, lap: Option<u32>) -> Option<std::path::PathBuf> {
+    match run.write(name, contents, kind, car, lap) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            eprintln!("Failed to write {}: {}", run.path().join(name).display(), e);
+            None
+        }
+    }
+}
+
+// Draws `graph` into `name` inside the run folder with the chosen renderer, unless images are off
+fn save_image(args: &Args, run: &mut RunDir, graph: &render::Graph, stem: &str, kind: &'static str, car: Option<usize>, lap: Option<u32>) -> bool {
+    let Some(format) = args.image else {
+        return true;
+    };
+    let name = format!("{}.{}", stem, format.extension());
+    match render::render(graph, format, args.renderer) {
+        Ok((image, engine)) => {
+            let Some(file) = save(run, &name, image, kind, car, lap) else {
+                return false;
+            };
+            let by = if engine == Engine::Graphviz { "Graphviz" } else { "the built-in renderer" };
+            println!("Rendered {} with {}", file.display().to_string().green(), by);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to render {}: {}", run.path().join(name).display(), e);
+            false
+        }
+    }
+}
+
+// `validate`: checks the track and, for every car, that the finish is within fuel range
+fn validate(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let race_data = &race.race_data;
+    print_summary(race_data);
+    if race_data.cars.is_empty() {
+        println!("{}", "Error: No car configurations provided in the input.".red());
+        return Exit::Infeasible;
+    }
+
+    let mut exit = Exit::Ok;
+    for &i in &race.cars {
+        let car_config = &race_data.cars[i];
+        let params = RaceParams { resolution: Resolution::EXACT, ..params_for(args, race_data, car_config) };
+        let diagnosis = diagnosis::diagnose(race_data, &race.graph, car_config, &params);
+        // Edges no tank can cover are only worth a warning when there is a way around them
+        let finishes = diagnosis::can_finish(race_data, &race.graph, car_config, &params);
+        let fatal = finishes != Some(true);
+        let verdict = match finishes {
+            Some(true) => "can finish".green(),
+            Some(false) => "cannot finish".red(),
+            None => "cannot be checked".red(),
+        };
+        println!("Car {}: {}", i + 1, verdict);
+        for cause in &diagnosis.causes {
+            let line = format!("  - {}", cause);
+            println!("{}", if fatal { line.red() } else { line.yellow() });
+        }
+        if fatal {
+            exit = Exit::Infeasible;
+        }
+    }
+    if exit == Exit::Ok {
+        println!("{}", "Track is valid.".green());
+    }
+    exit
+}
+
+// `solve`: prints each car's optimal strategy, with the optional analyses and exports
+fn solve(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let race_data = &race.race_data;
+    let graph = &race.graph;
+    print_summary(race_data);
+    if race_data.cars.is_empty() {
+        eprintln!("Error: No car configurations provided in the input.");
+        return Exit::Infeasible;
+    }
+
+    // The LP models and the JSON report go into a run folder, made only when one is asked for
+    let mut run = None;
+    if args.export_lp || args.json.is_some() {
+        run = create_run(args, "solve");
+        if run.is_none() {
+            return Exit::Output;
+        }
+    }
+
+    let mut exit = Exit::Ok;
+    let mut final_results = Vec::with_capacity(race.cars.len());
+    for (i, result) in solve_cars(args, &race) {
+        let car_config = &race_data.cars[i];
+        print_car_header(i, car_config);
+        exit = exit.max(exit_for(&result));
+
+        let params = params_for(args, race_data, car_config);
+        if !params.resolution.is_exact() {
+            println!("  Discretisation: fuel step {}, tyre step {} (status and time are for the discretised race)",
+                params.resolution.fuel, params.resolution.tyre);
+        }
+
+        if let Ok(outcome) = &result
+            && outcome.status != SolveStatus::Optimal
+        {
+            let bound = outcome.lower_bound.map(|b| b.to_string()).unwrap_or_else(|| "none".to_string());
+            println!("  Solve status: {} (lower bound {}, {} labels in {:.2?})",
+                outcome.status.to_string().yellow(), bound, outcome.stats.labels_created, outcome.stats.elapsed);
+        }
+        let result = result.and_then(|outcome| outcome.into_result());
+        final_results.push((i, result.clone()));
+
+        if args.report_gap && !params.resolution.is_exact() {
+            let gap = graph_rcspp::optimality_gap(race_data, graph, car_config, &params, &args.options, result.as_ref().ok());
+            match (gap.exact_time, gap.absolute(), gap.relative()) {
+                (Some(exact), Some(absolute), Some(relative)) => println!(
+                    "  Optimality gap vs exact solve: +{} units ({:.2}%), exact optimum {}",
+                    absolute.to_string().yellow(), relative * 100.0, exact
+                ),
+                (Some(exact), _, _) => println!("  Discretised solve found no strategy; exact optimum is {}", exact),
+                (None, _, _) => println!("  Exact solve found no strategy either"),
+            }
+            if let (Some(rounding), Some(replayed)) = (gap.rounding(), gap.replayed_time) {
+                println!("  Rounding: the strategy takes {} at exact tyre wear, {} less than its discretised time", replayed, rounding);
+            }
+            if gap.exact_status != SolveStatus::Optimal {
+                println!("  Exact solve stopped with status {}, so its optimum is not proven", gap.exact_status.to_string().yellow());
+            }
+        }
+
+        if args.pareto {
+            let front = pareto::find_pareto_front(race_data, graph, car_config, &params, &args.options);
+            visual::print_pareto_front(i, &front);
+        }
+
+        if let Some(run) = run.as_mut().filter(|_| args.export_lp) {
+            let mut model = Vec::new();
+            let written = ilp::write_lp(race_data, car_config, &params, &mut model)
+                .map_err(|e| eprintln!("Failed to export LP model for car {}: {}", i + 1, e))
+                .ok()
+                .and_then(|()| save(run, &format!("car{}.lp", i + 1), model, "lp-model", Some(i + 1), None));
+            match written {
+                Some(file) => println!("  Exported MIP model to {}", file.display().to_string().green()),
+                None => exit = exit.max(Exit::Output),
+            }
+        }
+
+        // Cross-check against an external MIP solver's solution, if one was provided for this car
+        if let Some(dir) = &args.lp_solutions {
+            let file = std::path::Path::new(dir).join(format!("car{}.sol", i + 1));
+            if let Ok(solution) = std::fs::read_to_string(&file) {
+                match ilp::import_solution(race_data, graph, car_config, &params, &solution) {
+                    Ok(imported) => {
+                        let solver_time = result.as_ref().map(|p| p.total_time.to_string()).unwrap_or_else(|_| "none".to_string());
+                        let agrees = result.as_ref().is_ok_and(|p| p.total_time == imported.total_time);
+                        println!("  MIP solution {}: time {} (label-setting {}) {}",
+                            file.display(), imported.total_time, solver_time,
+                            if agrees { "agrees".green() } else { "differs".yellow() });
+                        if let Err(discrepancies) = verify::verify_strategy(race_data, graph, car_config, &params, &imported) {
+                            for d in discrepancies {
+                                println!("    {}", d.to_string().red());
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to import {}: {}", file.display(), e),
+                }
+            }
+        }
+
+        match result {
+            Ok(path_info) => {
+                visual::print_race_strategy(i, &path_info, race_data);
+                if args.map {
+                    visual::print_strategy_map(race_data, params.start_node, params.end_node, &path_info);
+                }
+            }
+            Err(e) => {
+                println!("No feasible race strategy found for this car configuration ({}).", e);
+                // Only a genuinely infeasible race has constraints worth diagnosing
+                if !matches!(e, SolveError::BudgetExceeded | SolveError::TimeOverflow) {
+                    visual::print_diagnosis(&diagnosis::diagnose(race_data, graph, car_config, &params));
+                }
+            }
+        }
+    }
+
+    if let (Some(name), Some(run)) = (&args.json, run.as_mut()) {
+        match save(run, name, json::results_json(race_data, &final_results), "results-json", None, None) {
+            Some(file) => println!("\nWrote results JSON to {}", file.display().to_string().green()),
+            None => exit = exit.max(Exit::Output),
+        }
+    }
+    exit
+}
+
+// `render`: prints the track and writes the track DOT plus per-lap DOTs of every solved strategy,
+// each with an image unless `--image none`
+fn render(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let race_data = &race.race_data;
+    print_summary(race_data);
+    visual::print_graph_visualization(race_data, args.start, args.end.unwrap_or(race_data.n - 1));
+
+    let Some(mut run) = create_run(args, "render") else {
+        return Exit::Output;
+    };
+    let mut exit = Exit::Ok;
+    let track = visual::track_graph(race_data);
+    match save(&mut run, "track.dot", render::to_dot(&track), "track-dot", None, None) {
+        Some(file) => println!("Exported track graph DOT to {}", file.display().to_string().green()),
+        None => exit = Exit::Output,
+    }
+    if !save_image(args, &mut run, &track, "track", "track-image", None, None) {
+        exit = Exit::Output;
+    }
+    // The circuit to scale, with the start/finish line
+    let (start, end) = (args.start, args.end.unwrap_or(race_data.n - 1));
+    match save(&mut run, "circuit.svg", visual::circuit_svg(race_data, start, end, None), "circuit-map", None, None) {
+        Some(file) => println!("Drew the circuit map in {}", file.display().to_string().green()),
+        None => exit = Exit::Output,
+    }
+
+    let results = solve_cars(args, &race);
+    for (i, result) in results.iter().cloned() {
+        exit = exit.max(exit_for(&result));
+        let car_config = &race_data.cars[i];
+        match result.and_then(|outcome| outcome.into_result()) {
+            Ok(path_info) => {
+                for (lap, graph) in visual::lap_graphs(race_data, &path_info) {
+                    let stem = match lap {
+                        Some(lap) => format!("car{}_lap{}", i + 1, lap),
+                        None => format!("car{}_full", i + 1),
+                    };
+                    let saved = save(&mut run, &format!("{}.dot", stem), render::to_dot(&graph), "strategy-dot", Some(i + 1), lap).is_some();
+                    if !saved || !save_image(args, &mut run, &graph, &stem, "strategy-image", Some(i + 1), lap) {
+                        exit = exit.max(Exit::Output);
+                    }
+                }
+                let map = visual::circuit_svg(race_data, start, end, Some(&path_info));
+                if save(&mut run, &format!("car{}_circuit.svg", i + 1), map, "strategy-map", Some(i + 1), None).is_none() {
+                    exit = exit.max(Exit::Output);
+                }
+                println!("Saved car {}'s per-lap DOTs and circuit map", i + 1);
+            }
+            Err(SolveError::BudgetExceeded) => println!("Car {}: {}", i + 1, SolveError::BudgetExceeded),
+            Err(e) => {
+                println!("Car {}: {}", i + 1, e);
+                let diagnosis = diagnosis::diagnose(race_data, &race.graph, car_config, &params_for(args, race_data, car_config));
+                let graph = visual::diagnosis_graph(race_data, &diagnosis);
+                let stem = format!("car{}_infeasible", i + 1);
+                match save(&mut run, &format!("{}.dot", stem), render::to_dot(&graph), "diagnosis-dot", Some(i + 1), None) {
+                    Some(file) => println!("Highlighted offending nodes and edges in {}", file.display().to_string().green()),
+                    None => exit = exit.max(Exit::Output),
+                }
+                if !save_image(args, &mut run, &graph, &stem, "diagnosis-image", Some(i + 1), None) {
+                    exit = exit.max(Exit::Output);
+                }
+            }
+        }
+    }
+
+    // Everything above in one offline page; the resolution only matters to the solver
+    let params = RaceParams {
+        start_node: start,
+        end_node: end,
+        total_laps: args.laps,
+        pit_stop_penalty: args.pit_penalty,
+        tyre_wear_distance: args.tyre_wear,
+        resolution: Resolution::EXACT,
+    };
+    let html = report::html_report(&output::scenario_name(&args.input), race_data, &params, &results);
+    match save(&mut run, "report.html", html, "html-report", None, None) {
+        Some(file) => println!("Wrote the HTML report to {}", file.display().to_string().green()),
+        None => exit = exit.max(Exit::Output),
+    }
+    println!("Listed {} artefacts in {}", run.artefacts().len(), run.path().join(output::MANIFEST).display().to_string().green());
+    exit
+}
+
+// `replay`: animates each car's strategy, one node at a time
+fn replay(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let race_data = &race.race_data;
+    let start_node = args.start;
+
+    let mut exit = Exit::Ok;
+    let mut strategies = Vec::new();
+    for (i, result) in solve_cars(args, &race) {
+        exit = exit.max(exit_for(&result));
+        let car_config = &race_data.cars[i];
+        let color = car_color(i);
+        let path_info = match result.and_then(|outcome| outcome.into_result()) {
+            Ok(path_info) => path_info,
+            Err(e) => {
+                println!("\nCar {}: {}", (i + 1).to_string().color(color), e);
+                continue;
+            }
+        };
+
+        println!("\n--- Visualizing Car {}'s Journey ---", (i + 1).to_string().color(color));
+        let print_position = |node: usize, fuel: Fixed, tyre_distance: Fixed| {
+            let node_display = if race_data.pit_nodes.contains(&node) {
+                node.to_string().color(Color::Red).to_string()
+            } else {
+                node.to_string().color(color).to_string()
+            };
+            println!("  Car {} at Node {}. Fuel: {}, Tyre Distance: {}",
+                (i + 1).to_string().color(color),
+                node_display,
+                fuel.to_string().color(Color::Cyan),
+                tyre_distance.to_string().color(Color::Yellow)
+            );
+        };
+
+        // Replay the solver's own timeline rather than re-deriving fuel and tyre state
+        let initial_fuel = path_info.segments.first().map(|s| s.fuel_before).unwrap_or(car_config.fuel_capacity);
+        print_position(start_node, initial_fuel, Fixed::ZERO);
+        thread::sleep(args.delay); // Pause for readability
+
+        for segment in &path_info.segments {
+            if segment.pit_stop {
+                println!("  {} at Node {}. Refueling and changing tires.", "PIT STOP!".color(Color::Red), segment.from.to_string().color(Color::Red));
+                thread::sleep(args.delay / 2); // Short pause for pit stop
+            }
+
+            print_position(segment.to, segment.fuel_after, segment.tyre_after);
+
+            if segment.to == start_node && segment.from != start_node { // Lap completed at the start node
+                println!("  --- Entering Lap {} ---", (segment.lap + 2).to_string().color(Color::White));
+            }
+
+            thread::sleep(args.delay); // Pause for readability
         }
+        println!("--- Journey Complete for Car {} ---", (i + 1).to_string().color(color));
+        visual::print_race_charts(i, &path_info, car_config);
+        strategies.push((i, path_info));
+    }
+
+    // Every car on the same charts, to compare how they spend fuel and tyres
+    if strategies.len() > 1 {
+        println!("\n--- All Cars ---");
+        let cars: Vec<_> = strategies.iter().map(|(i, path_info)| (*i, path_info, &race_data.cars[*i])).collect();
+        visual::print_overlay_charts(&cars, args.tyre_wear);
     }
+    exit
+}
+
+// `tui`: the full-screen alternative to `replay`, stepping through each car's strategy and
+// re-solving as the race parameters change; `--delay` paces its playback
+fn run_tui(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let Some(car_config) = race.cars.first().map(|&i| race.race_data.cars[i].clone()) else {
+        eprintln!("Error: No car configurations provided in the input.");
+        return Exit::Infeasible;
+    };
+    let params = params_for(args, &race.race_data, &car_config);
+    let buckets = match args.resolution {
+        ResolutionOption::Buckets(buckets) => Some(buckets),
+        ResolutionOption::Steps { .. } => None,
+    };
+    let mut app = tui::App::new(args.input.clone(), race.race_data, race.cars, params, buckets, args.options);
+    if let Err(e) = tui::run(&mut app, args.delay) {
+        eprintln!("Terminal UI failed: {}", e);
+        return Exit::Output;
+    }
+    app.results.iter().map(exit_for).max().unwrap_or(Exit::Ok)
+}
+
+// `compare`: ranks the cars by race time with their pit stops and resource use
+fn compare(args: &Args) -> Exit {
+    let race = match load(args) {
+        Ok(race) => race,
+        Err(exit) => return exit,
+    };
+    let race_data = &race.race_data;
+
+    let mut exit = Exit::Ok;
+    let mut rows = Vec::new();
+    for (i, result) in solve_cars(args, &race) {
+        exit = exit.max(exit_for(&result));
+        let status = match &result {
+            Ok(outcome) => outcome.status.to_string(),
+            Err(e) => e.to_string(),
+        };
+        rows.push((i, result.ok().and_then(|outcome| outcome.best), status));
+    }
+    // Fastest first, cars without a strategy last in input order
+    rows.sort_by_key(|(i, best, _)| (best.as_ref().map_or(Fixed::MAX, |p| p.total_time), *i));
+    let fastest = rows.first().and_then(|(_, best, _)| best.as_ref().map(|p| p.total_time));
+
+    println!("{:<4} {:<5} {:>10} {:>10} {:>12} {:>10} {:>5} {:>12}  status", "rank", "car", "fuel", "tyre cost", "time", "gap", "pits", "tyre penalty");
+    for (rank, (i, best, status)) in rows.iter().enumerate() {
+        let car_config = &race_data.cars[*i];
+        let car = format!("{:<5}", i + 1).color(car_color(*i));
+        match best {
+            Some(p) => {
+                let gap = fastest.map(|f| format!("+{}", p.total_time - f)).unwrap_or_default();
+                let tyre_penalty = p.segments.iter().fold(Fixed::ZERO, |sum, s| sum + s.tyre_penalty);
+                println!("{:<4} {} {:>10} {:>10} {:>12} {:>10} {:>5} {:>12}  {}", rank + 1, car, car_config.fuel_capacity.to_string(),
+                    car_config.tyre_cost.to_string(), p.total_time.to_string(), gap, p.pit_stops.len(), tyre_penalty.to_string(), status);
+            }
+            None => println!("{:<4} {} {:>10} {:>10} {:>12} {:>10} {:>5} {:>12}  {}", "-", car, car_config.fuel_capacity.to_string(),
+                car_config.tyre_cost.to_string(), "-", "-", "-", "-", status.red()),
+        }
+    }
+
+    // With two or more strategies, line them up lap by lap against the fastest
+    let strategies: Vec<(usize, &PathInfo)> = rows.iter().filter_map(|(i, best, _)| best.as_ref().map(|p| (*i, p))).collect();
+    if let Some(comparison) = compare_strategies(&strategies) {
+        visual::print_comparison(&comparison);
+    }
+    exit
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let program = args.first().map(String::as_str).unwrap_or("f1-track");
+    let Some(first) = args.get(1) else {
+        usage(program);
+        return Exit::Usage.into();
+    };
+    if matches!(first.as_str(), "help" | "--help" | "-h") {
+        usage(program);
+        return Exit::Ok.into();
+    }
+
+    if first == "generate" {
+        return match generate(&args[2..]) {
+            Ok(exit) => exit.into(),
+            Err(message) => {
+                eprintln!("Error: {}", message);
+                usage(program);
+                Exit::Usage.into()
+            }
+        };
+    }
+
+    // A bare input file is shorthand for `solve`
+    let (command, rest) = match Command::parse(first) {
+        Some(command) => (command, &args[2..]),
+        None => (Command::Solve, &args[1..]),
+    };
+    let parsed = match parse_args(command, rest) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            usage(program);
+            return Exit::Usage.into();
+        }
+    };
+    let exit = match command {
+        Command::Validate => validate(&parsed),
+        Command::Solve => {
+            println!("F1 Track Optimization System");
+            solve(&parsed)
+        }
+        Command::Render => render(&parsed),
+        Command::Replay => replay(&parsed),
+        Command::Compare => compare(&parsed),
+        Command::Tui => run_tui(&parsed),
+    };
+    exit.into()
 }
@@ -0,0 +1,178 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+// File every run folder gets, listing the artefacts written into it
+pub const MANIFEST: &str = "manifest.json";
+
+// What to do when the run folder already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    // Refuse to touch it
+    Error,
+    // Delete it first, but only if it holds a manifest from an earlier run
+    Replace,
+    // Use the first free name with a -2, -3, ... suffix
+    Increment,
+}
+
+impl FromStr for Overwrite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Overwrite::Error),
+            "replace" => Ok(Overwrite::Replace),
+            "increment" => Ok(Overwrite::Increment),
+            _ => Err(format!("unknown overwrite policy '{}' (expected error, replace or increment)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    // Folder that holds one subfolder per run
+    pub root: PathBuf,
+    // Subfolder name; defaults to the scenario name and a UTC timestamp
+    pub run_name: Option<String>,
+    pub overwrite: Overwrite,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions { root: PathBuf::from("output"), run_name: None, overwrite: Overwrite::Increment }
+    }
+}
+
+// One file produced by a run
+#[derive(Debug, Clone, Serialize)]
+pub struct Artefact {
+    // Relative to the run folder when written inside it, absolute otherwise
+    pub path: PathBuf,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub car: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lap: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Manifest {
+    scenario: String,
+    input: String,
+    command: String,
+    // Seconds since the Unix epoch
+    created: u64,
+    artefacts: Vec<Artefact>,
+}
+
+// A run's output folder. Files are written through it so the manifest lists every one of them.
+#[derive(Debug)]
+pub struct RunDir {
+    path: PathBuf,
+    manifest: Manifest,
+}
+
+impl RunDir {
+    // Creates the run folder under `options.root`, applying the overwrite policy
+    pub fn create(options: &OutputOptions, input: &str, command: &str) -> io::Result<RunDir> {
+        let scenario = scenario_name(input);
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let name = match &options.run_name {
+            Some(name) => name.clone(),
+            None => format!("{}-{}", scenario, timestamp(created)),
+        };
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid run name '{}'", name)));
+        }
+
+        let mut path = options.root.join(&name);
+        if path.exists() {
+            match options.overwrite {
+                Overwrite::Error => {
+                    return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display())));
+                }
+                Overwrite::Replace if path.join(MANIFEST).is_file() => fs::remove_dir_all(&path)?,
+                Overwrite::Replace => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} exists but is not a previous run (no {}), refusing to replace it", path.display(), MANIFEST),
+                    ));
+                }
+                Overwrite::Increment => {
+                    let mut suffix = 2;
+                    while path.exists() {
+                        path = options.root.join(format!("{}-{}", name, suffix));
+                        suffix += 1;
+                    }
+                }
+            }
+        }
+        fs::create_dir_all(&path)?;
+
+        let manifest = Manifest { scenario, input: input.to_string(), command: command.to_string(), created, artefacts: Vec::new() };
+        let run = RunDir { path, manifest };
+        run.write_manifest()?;
+        Ok(run)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn artefacts(&self) -> &[Artefact] {
+        &self.manifest.artefacts
+    }
+
+    // Writes `contents` to `name` inside the run folder and lists it in the manifest
+    pub fn write(&mut self, name: &str, contents: impl AsRef<[u8]>, kind: &'static str, car: Option<usize>, lap: Option<u32>) -> io::Result<PathBuf> {
+        let path = self.path.join(name);
+        fs::write(&path, contents)?;
+        self.record(&path, kind, car, lap)?;
+        Ok(path)
+    }
+
+    // Lists a file written by other means, e.g. an image rendered from a DOT file
+    pub fn record(&mut self, path: &Path, kind: &'static str, car: Option<usize>, lap: Option<u32>) -> io::Result<()> {
+        let path = match path.strip_prefix(&self.path) {
+            Ok(inside) => inside.to_path_buf(),
+            Err(_) => std::path::absolute(path)?,
+        };
+        self.manifest.artefacts.retain(|a| a.path != path);
+        self.manifest.artefacts.push(Artefact { path, kind, car, lap });
+        // Rewritten every time, so the manifest stays accurate if the run stops part way
+        self.write_manifest()
+    }
+
+    fn write_manifest(&self) -> io::Result<()> {
+        let mut json = serde_json::to_string_pretty(&self.manifest).map_err(io::Error::other)?;
+        json.push('\n');
+        fs::write(self.path.join(MANIFEST), json)
+    }
+}
+
+// Input file stem with anything unsafe in a folder name replaced
+pub fn scenario_name(input: &str) -> String {
+    let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    if name.is_empty() { "run".to_string() } else { name }
+}
+
+// `YYYYMMDD-HHMMSS` in UTC
+pub fn timestamp(secs: u64) -> String {
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", year, month, day, rest / 3600, rest % 3600 / 60, rest % 60)
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
+use crate::graph_rcspp::{tyre_penalty, Graph, PathInfo, RaceParams, SolveOptions, State};
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+
+// Objectives traded off against each other; smaller is better for all three
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Objectives {
+    pub total_time: Fixed,
+    pub pit_stops: usize,
+    pub fuel_used: Fixed,
+}
+
+impl Objectives {
+    // At least as good in every objective
+    fn covers(&self, other: &Objectives) -> bool {
+        self.total_time <= other.total_time && self.pit_stops <= other.pit_stops && self.fuel_used <= other.fuel_used
+    }
+}
+
+// One non-dominated strategy
+#[derive(Debug, Clone)]
+pub struct ParetoPoint {
+    pub objectives: Objectives,
+    pub path_info: PathInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParetoFront {
+    // Sorted by total time
+    pub points: Vec<ParetoPoint>,
+    // False when a budget stopped the search, so the front may be missing points
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Label {
+    objectives: Objectives,
+    state: State,
+    path: Vec<usize>,
+    pit_stops_taken: Vec<(u32, usize)>,
+    id: usize,
+}
+
+impl Ord for Label {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Min-heap on time, ties broken towards fewer stops and less fuel
+        other.objectives.total_time.cmp(&self.objectives.total_time)
+            .then(other.objectives.pit_stops.cmp(&self.objectives.pit_stops))
+            .then(other.objectives.fuel_used.cmp(&self.objectives.fuel_used))
+    }
+}
+
+impl PartialOrd for Label {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Multi-objective label-setting search: the same moves as `find_optimal_path`, but each state keeps
+// every label not dominated on (time, pit stops, fuel used) instead of only the fastest one.
+pub fn find_pareto_front(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    options: &SolveOptions,
+) -> ParetoFront {
+    let started = Instant::now();
+    let RaceParams { start_node, end_node, total_laps, pit_stop_penalty, tyre_wear_distance, resolution } = *params;
+    let mut heap = BinaryHeap::new();
+    // Live (objectives, label id) pairs per state
+    let mut frontier: HashMap<State, Vec<(Objectives, usize)>> = HashMap::new();
+    let mut finished: Vec<Label> = Vec::new();
+    let mut next_id = 0;
+    let mut complete = true;
+
+    let initial_state = State {
+        lap: 0,
+        current_node: start_node,
+        current_fuel: car_config.fuel_capacity.floor_to(resolution.fuel),
+        tyre_distance: Fixed::ZERO,
+    };
+    let zero = Objectives { total_time: Fixed::ZERO, pit_stops: 0, fuel_used: Fixed::ZERO };
+    frontier.insert(initial_state.clone(), vec![(zero, next_id)]);
+    heap.push(Label { objectives: zero, state: initial_state, path: vec![start_node], pit_stops_taken: Vec::new(), id: next_id });
+    next_id += 1;
+
+    while let Some(label) = heap.pop() {
+        let over_budget = options.timeout.is_some_and(|t| started.elapsed() >= t)
+            || options.max_labels.is_some_and(|max| next_id > max)
+            || options.max_heap.is_some_and(|max| heap.len() + 1 > max);
+        if over_budget {
+            complete = false;
+            break;
+        }
+        // Skip labels that were dominated after being queued
+        if !frontier.get(&label.state).is_some_and(|live| live.iter().any(|&(_, id)| id == label.id)) {
+            continue;
+        }
+        // Objectives only grow along a path, so anything covered by a finished strategy is done
+        if finished.iter().any(|f| f.objectives.covers(&label.objectives)) {
+            continue;
+        }
+        if label.state.current_node == end_node && label.state.lap + 1 == total_laps {
+            finished.retain(|f| !label.objectives.covers(&f.objectives));
+            finished.push(label);
+            continue;
+        }
+
+        let state = &label.state;
+        let is_pit_node = race_data.pit_nodes.contains(&state.current_node);
+        for &(neighbor, distance) in &graph.adj[&state.current_node] {
+            let lap = if neighbor == start_node && state.current_node != start_node { state.lap + 1 } else { state.lap };
+            // Crossing the start line after the final lap can never lead to the finish
+            if lap >= total_laps {
+                continue;
+            }
+            // (pit first?, fuel and tyre wear on departure)
+            let mut moves = Vec::with_capacity(2);
+            if state.current_fuel >= distance {
+                moves.push((false, state.current_fuel, state.tyre_distance));
+            }
+            if is_pit_node && car_config.fuel_capacity >= distance {
+                moves.push((true, car_config.fuel_capacity, Fixed::ZERO));
+            }
+            for (pit, fuel, tyre) in moves {
+                let new_tyre = (tyre + distance).ceil_to(resolution.tyre);
+                let tyre_penalty = tyre_penalty(new_tyre, tyre_wear_distance, car_config.tyre_cost);
+                // A race too long for `Fixed` cannot be on the front
+                let Some(total_time) = label.objectives.total_time
+                    .checked_add(distance)
+                    .and_then(|t| t.checked_add(tyre_penalty))
+                    .and_then(|t| t.checked_add(if pit { pit_stop_penalty } else { Fixed::ZERO }))
+                else {
+                    continue;
+                };
+                let objectives = Objectives {
+                    total_time,
+                    pit_stops: label.objectives.pit_stops + pit as usize,
+                    fuel_used: label.objectives.fuel_used + distance,
+                };
+                let new_state = State {
+                    lap,
+                    current_node: neighbor,
+                    current_fuel: (fuel - distance).floor_to(resolution.fuel),
+                    tyre_distance: new_tyre,
+                };
+                let live = frontier.entry(new_state.clone()).or_default();
+                if live.iter().any(|(o, _)| o.covers(&objectives)) {
+                    continue;
+                }
+                live.retain(|(o, _)| !objectives.covers(o));
+                live.push((objectives, next_id));
+
+                let mut path = label.path.clone();
+                path.push(neighbor);
+                let mut pit_stops_taken = label.pit_stops_taken.clone();
+                if pit {
+                    pit_stops_taken.push((state.lap, state.current_node));
+                }
+                heap.push(Label { objectives, state: new_state, path, pit_stops_taken, id: next_id });
+                next_id += 1;
+            }
+        }
+    }
+
+    let mut points: Vec<ParetoPoint> = finished
+        .into_iter()
+        .map(|label| {
+            // The verifier's replay is the authoritative timeline for a node sequence and its pit stops
+            let (segments, _) = crate::verify::replay(race_data, graph, car_config, params, &label.path, &label.pit_stops_taken);
+            ParetoPoint {
+                objectives: label.objectives,
+                path_info: PathInfo {
+                    total_time: label.objectives.total_time,
+                    pit_stops: label.pit_stops_taken,
+                    node_sequence: label.path,
+                    segments,
+                },
+            }
+        })
+        .collect();
+    points.sort_by_key(|p| (p.objectives.total_time, p.objectives.pit_stops, p.objectives.fuel_used));
+    ParetoFront { points, complete }
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+use std::io;
+use std::str::FromStr;
+use graphviz_rust::cmd::Format;
+use graphviz_rust::dot_structures::{Attribute, Edge, EdgeTy, GraphAttributes, Id, Node, NodeId, Stmt, Vertex};
+pub use graphviz_rust::dot_structures::Graph;
+use graphviz_rust::printer::{DotPrinter, PrinterContext};
+
+// Image formats a graph can be rendered to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    // Needs Graphviz; the built-in renderer only draws SVG
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(format!("unknown image format '{}' (expected svg or png)", s)),
+        }
+    }
+}
+
+// Which renderer draws the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    // Graphviz when it is installed, the built-in renderer otherwise
+    Auto,
+    Graphviz,
+    Builtin,
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Engine::Auto),
+            "graphviz" => Ok(Engine::Graphviz),
+            "builtin" => Ok(Engine::Builtin),
+            _ => Err(format!("unknown renderer '{}' (expected auto, graphviz or builtin)", s)),
+        }
+    }
+}
+
+// Renders `graph` to an image, returning its bytes and the engine that drew it. A `dot` run that
+// fails is an error with Graphviz's message; only a missing `dot` falls back to the built-in renderer.
+pub fn render(graph: &Graph, format: ImageFormat, engine: Engine) -> io::Result<(Vec<u8>, Engine)> {
+    if engine != Engine::Builtin {
+        let mut ctx = PrinterContext::default();
+        let format = match format {
+            ImageFormat::Svg => Format::Svg,
+            ImageFormat::Png => Format::Png,
+        };
+        match graphviz_rust::exec(graph.clone(), &mut ctx, vec![format.into()]) {
+            Ok(image) => return Ok((image, Engine::Graphviz)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && engine == Engine::Auto => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "Graphviz `dot` is not installed"));
+            }
+            Err(e) => return Err(io::Error::other(format!("Graphviz failed: {}", e.to_string().trim()))),
+        }
+    }
+    match format {
+        ImageFormat::Svg => Ok((builtin_svg(graph).into_bytes(), Engine::Builtin)),
+        ImageFormat::Png => Err(io::Error::new(io::ErrorKind::Unsupported, "PNG output needs Graphviz `dot`, which is not installed")),
+    }
+}
+
+// Graph in DOT notation, one statement per line
+pub fn to_dot(graph: &Graph) -> String {
+    let mut dot = graph.print(PrinterContext::default().with_semi());
+    dot.push('\n');
+    dot
+}
+
+pub fn digraph(stmts: Vec<Stmt>) -> Graph {
+    Graph::DiGraph { id: Id::Plain("Track".to_string()), strict: false, stmts }
+}
+
+// An attribute, quoting the value unless it is a plain DOT identifier or number. Backslash escapes
+// such as `\n` are passed through for Graphviz to interpret.
+pub fn attr(key: &str, value: impl ToString) -> Attribute {
+    let value = value.to_string();
+    let plain = !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    let value = if plain { Id::Plain(value) } else { Id::Escaped(format!("\"{}\"", value.replace('"', "\\\""))) };
+    Attribute(Id::Plain(key.to_string()), value)
+}
+
+pub fn node(id: usize, attributes: Vec<Attribute>) -> Stmt {
+    Stmt::Node(Node::new(NodeId(Id::Plain(id.to_string()), None), attributes))
+}
+
+pub fn edge(u: usize, v: usize, attributes: Vec<Attribute>) -> Stmt {
+    let vertex = |id: usize| Vertex::N(NodeId(Id::Plain(id.to_string()), None));
+    Stmt::Edge(Edge { ty: EdgeTy::Pair(vertex(u), vertex(v)), attributes })
+}
+
+// Default node attributes for every node in the graph
+pub fn node_defaults(attributes: Vec<Attribute>) -> Stmt {
+    Stmt::GAttribute(GraphAttributes::Node(attributes))
+}
+
+// The text of an attribute value, without DOT quoting
+fn id_text(id: &Id) -> String {
+    match id {
+        Id::Escaped(s) => s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s).replace("\\\"", "\""),
+        Id::Html(s) | Id::Plain(s) | Id::Anonymous(s) => s.clone(),
+    }
+}
+
+fn lookup<'a>(attributes: &'a [Attribute], key: &str) -> Option<&'a Id> {
+    attributes.iter().rev().find(|a| id_text(&a.0) == key).map(|a| &a.1)
+}
+
+// Escapes text for SVG content and attribute values
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Graphviz colour names that SVG does not know
+fn svg_color(color: &str) -> String {
+    let grey = color.strip_prefix("gray").or_else(|| color.strip_prefix("grey"));
+    match grey.and_then(|level| level.parse::<u32>().ok()) {
+        Some(level) if level <= 100 => {
+            let v = (level * 255 + 50) / 100;
+            format!("#{:02x}{:02x}{:02x}", v, v, v)
+        }
+        _ => color.to_string(),
+    }
+}
+
+struct DrawnNode {
+    label: String,
+    attributes: Vec<Attribute>,
+}
+
+struct DrawnEdge {
+    from: usize,
+    to: usize,
+    attributes: Vec<Attribute>,
+}
+
+const NODE_RADIUS: f64 = 20.0;
+const MARGIN: f64 = 120.0;
+const LINE_HEIGHT: f64 = 13.0;
+
+// A pure-Rust stand-in for Graphviz: nodes evenly spaced on a circle in declaration order (tracks
+// are loops, so consecutive nodes end up next to each other), edges as straight or bowed arrows
+// with their labels. Honours the node and edge attributes the track DOTs use: label, shape,
+// fillcolor, color, fontcolor, penwidth and style=dashed.
+pub fn builtin_svg(graph: &Graph) -> String {
+    let stmts = match graph {
+        Graph::Graph { stmts, .. } | Graph::DiGraph { stmts, .. } => stmts,
+    };
+    let mut defaults = Vec::new();
+    let mut nodes: Vec<DrawnNode> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut edges = Vec::new();
+    let mut add_node = |name: String, nodes: &mut Vec<DrawnNode>| -> usize {
+        *index.entry(name.clone()).or_insert_with(|| {
+            nodes.push(DrawnNode { label: name, attributes: Vec::new() });
+            nodes.len() - 1
+        })
+    };
+    for stmt in stmts {
+        match stmt {
+            Stmt::GAttribute(GraphAttributes::Node(attributes)) => defaults.extend(attributes.iter().cloned()),
+            Stmt::Node(node) => {
+                let i = add_node(id_text(&node.id.0), &mut nodes);
+                nodes[i].attributes.extend(node.attributes.iter().cloned());
+            }
+            Stmt::Edge(Edge { ty: EdgeTy::Pair(Vertex::N(u), Vertex::N(v)), attributes }) => {
+                let from = add_node(id_text(&u.0), &mut nodes);
+                let to = add_node(id_text(&v.0), &mut nodes);
+                edges.push(DrawnEdge { from, to, attributes: attributes.clone() });
+            }
+            _ => {}
+        }
+    }
+
+    let n = nodes.len().max(1);
+    let radius = (110.0 * n as f64 / (2.0 * PI)).max(if n == 1 { 0.0 } else { 120.0 });
+    let size = 2.0 * (radius + MARGIN);
+    let position = |i: usize| {
+        let angle = -PI / 2.0 + 2.0 * PI * i as f64 / n as f64;
+        (size / 2.0 + radius * angle.cos(), size / 2.0 + radius * angle.sin())
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="Helvetica, Arial, sans-serif" font-size="11">"#, size, size, size, size);
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    // Edges between the same pair of nodes bow out to either side so they stay apart
+    let mut pairs: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (k, e) in edges.iter().enumerate() {
+        pairs.entry((e.from.min(e.to), e.from.max(e.to))).or_default().push(k);
+    }
+    let mut labels = String::new();
+    for (k, e) in edges.iter().enumerate() {
+        let text = |key: &str| lookup(&e.attributes, key).map(id_text);
+        let color = svg_color(&text("color").unwrap_or_else(|| "black".to_string()));
+        let font_color = text("fontcolor").map(|c| svg_color(&c)).unwrap_or_else(|| "black".to_string());
+        let width = text("penwidth").and_then(|w| w.parse::<f64>().ok()).unwrap_or(1.0);
+        let dash = if text("style").is_some_and(|s| s.contains("dashed")) { r#" stroke-dasharray="6,4""# } else { "" };
+
+        let (start, end, control) = if e.from == e.to {
+            // Self loop: a teardrop outside the circle
+            let (x, y) = position(e.from);
+            let (dx, dy) = unit(x - size / 2.0, y - size / 2.0).unwrap_or((0.0, -1.0));
+            let (px, py) = (-dy, dx);
+            let start = (x + (dx * 0.7 + px * 0.7) * NODE_RADIUS, y + (dy * 0.7 + py * 0.7) * NODE_RADIUS);
+            let end = (x + (dx * 0.7 - px * 0.7) * NODE_RADIUS, y + (dy * 0.7 - py * 0.7) * NODE_RADIUS);
+            (start, end, (x + dx * NODE_RADIUS * 4.0, y + dy * NODE_RADIUS * 4.0))
+        } else {
+            let (a, b) = (position(e.from), position(e.to));
+            let group = &pairs[&(e.from.min(e.to), e.from.max(e.to))];
+            let slot = group.iter().position(|&j| j == k).unwrap_or(0) as f64 - (group.len() as f64 - 1.0) / 2.0;
+            // Offsets are measured against the pair's fixed direction, so opposite edges separate too
+            let (lo, hi) = if e.from < e.to { (a, b) } else { (b, a) };
+            let (dx, dy) = unit(hi.0 - lo.0, hi.1 - lo.1).unwrap_or((1.0, 0.0));
+            let bow = slot * 45.0;
+            let control = ((a.0 + b.0) / 2.0 - dy * bow, (a.1 + b.1) / 2.0 + dx * bow);
+            let trim = |from: (f64, f64), toward: (f64, f64)| {
+                let (ux, uy) = unit(toward.0 - from.0, toward.1 - from.1).unwrap_or((0.0, 0.0));
+                (from.0 + ux * NODE_RADIUS, from.1 + uy * NODE_RADIUS)
+            };
+            (trim(a, control), trim(b, control), control)
+        };
+
+        let _ = writeln!(
+            svg,
+            r#"<path d="M {:.1} {:.1} Q {:.1} {:.1} {:.1} {:.1}" fill="none" stroke="{}" stroke-width="{}"{}/>"#,
+            start.0, start.1, control.0, control.1, end.0, end.1, xml_escape(&color), width, dash
+        );
+        // Arrowhead along the curve's final direction
+        if let Some((ux, uy)) = unit(end.0 - control.0, end.1 - control.1) {
+            let size = 8.0 + width;
+            let back = (end.0 - ux * size, end.1 - uy * size);
+            let _ = writeln!(
+                svg,
+                r#"<polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="{}"/>"#,
+                end.0, end.1, back.0 - uy * size / 2.5, back.1 + ux * size / 2.5, back.0 + uy * size / 2.5, back.1 - ux * size / 2.5,
+                xml_escape(&color)
+            );
+        }
+        if let Some(label) = text("label") {
+            // The curve's midpoint, nudged off the line and growing away from the centre
+            let mid = (0.25 * start.0 + 0.5 * control.0 + 0.25 * end.0, 0.25 * start.1 + 0.5 * control.1 + 0.25 * end.1);
+            let (x, anchor) = if mid.0 >= size / 2.0 { (mid.0 + 4.0, "start") } else { (mid.0 - 4.0, "end") };
+            write_text(&mut labels, x, mid.1 - 4.0, &label, &font_color, anchor);
+        }
+    }
+
+    for (i, node) in nodes.iter().enumerate() {
+        let text = |key: &str| lookup(&node.attributes, key).or_else(|| lookup(&defaults, key)).map(id_text);
+        let (x, y) = position(i);
+        let filled = text("style").is_some_and(|s| s.contains("filled"));
+        let fill = if filled { svg_color(&text("fillcolor").unwrap_or_else(|| "lightgrey".to_string())) } else { "white".to_string() };
+        let stroke = svg_color(&text("color").unwrap_or_else(|| "black".to_string()));
+        match text("shape").as_deref() {
+            Some("box") | Some("rect") | Some("rectangle") | Some("square") => {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" stroke="{}"/>"#,
+                    x - NODE_RADIUS, y - NODE_RADIUS * 0.8, NODE_RADIUS * 2.0, NODE_RADIUS * 1.6, xml_escape(&fill), xml_escape(&stroke)
+                );
+            }
+            _ => {
+                let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}" stroke="{}"/>"#, x, y, NODE_RADIUS, xml_escape(&fill), xml_escape(&stroke));
+            }
+        }
+        let label = text("label").unwrap_or_else(|| node.label.clone());
+        let font_color = svg_color(&text("fontcolor").unwrap_or_else(|| "black".to_string()));
+        write_text(&mut svg, x, y + 4.0, &label, &font_color, "middle");
+    }
+    // Labels go last so lines and nodes never hide them
+    svg.push_str(&labels);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Multi-line text, splitting on DOT's `\n` escape
+fn write_text(svg: &mut String, x: f64, y: f64, text: &str, color: &str, anchor: &str) {
+    let lines: Vec<&str> = text.split("\\n").collect();
+    let top = y - (lines.len() as f64 - 1.0) * LINE_HEIGHT / 2.0;
+    let _ = write!(svg, r#"<text x="{:.1}" y="{:.1}" fill="{}" text-anchor="{}">"#, x, top, xml_escape(color), anchor);
+    for (i, line) in lines.iter().enumerate() {
+        let dy = if i == 0 { 0.0 } else { LINE_HEIGHT };
+        let _ = write!(svg, r#"<tspan x="{:.1}" dy="{}">{}</tspan>"#, x, dy, xml_escape(line));
+    }
+    svg.push_str("</text>\n");
+}
+
+fn unit(dx: f64, dy: f64) -> Option<(f64, f64)> {
+    let length = (dx * dx + dy * dy).sqrt();
+    (length > 1e-9).then(|| (dx / length, dy / length))
+}
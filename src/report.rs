@@ -0,0 +1,390 @@
+use std::fmt::Write;
+use crate::comparison::{compare_strategies, describe, pit_list, signed, Comparison};
+use crate::graph_rcspp::{PathInfo, RaceParams, SolveError, SolveOutcome};
+use crate::input_parser::RaceData;
+use crate::render::xml_escape;
+use crate::units::Fixed;
+use crate::visual::{self, ProfilePoint};
+
+// Car colours in reports, matching the terminal's green, blue and magenta first
+pub const CAR_COLORS: [&str; 6] = ["#2e7d32", "#1565c0", "#8e24aa", "#ef6c00", "#c62828", "#00838f"];
+
+pub fn car_color(car: usize) -> &'static str {
+    CAR_COLORS[car % CAR_COLORS.len()]
+}
+
+const STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1000px; color: #222; }
+h1, h2, h3 { margin-bottom: 0.3em; }
+section { margin-bottom: 2.5em; }
+table { border-collapse: collapse; margin: 0.6em 0; font-size: 13px; }
+th, td { border: 1px solid #ccc; padding: 3px 8px; text-align: right; }
+th { background: #f0f0f0; }
+td.left, th.left { text-align: left; }
+tr.pit td { background: #fdecea; }
+td.differ { background: #fff3cd; font-weight: bold; }
+.muted { color: #777; }
+.error { color: #c62828; font-weight: bold; }
+.charts { display: flex; flex-wrap: wrap; gap: 1em; }
+details { margin: 0.3em 0; }
+svg { max-width: 100%; height: auto; }
+";
+
+// A single self-contained HTML page for one run: the race settings, the circuit map, a comparison
+// of every car, then each car's strategy summary, map, fuel and tyre charts and per-lap tables.
+// Everything is inline, so the file opens offline and can be mailed around on its own.
+pub fn html_report(scenario: &str, race_data: &RaceData, params: &RaceParams, cars: &[(usize, Result<SolveOutcome, SolveError>)]) -> String {
+    let mut html = String::new();
+    let title = format!("Race report: {}", scenario);
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{}</title>\n<style>{}</style>\n</head>\n<body>", xml_escape(&title), STYLE);
+    let _ = writeln!(html, "<h1>{}</h1>", xml_escape(&title));
+    let _ = writeln!(
+        html,
+        "<p>{} nodes, {} edges, {} pit nodes. From node {} to node {} over {} lap(s), pit penalty {}, tyre wear from {}.</p>",
+        race_data.n, race_data.m, race_data.pit_nodes.len(), params.start_node, params.end_node, params.total_laps,
+        params.pit_stop_penalty, params.tyre_wear_distance
+    );
+
+    let _ = writeln!(html, "<section>\n<h2>Track</h2>");
+    html.push_str(&visual::circuit_svg(race_data, params.start_node, params.end_node, None));
+    let _ = writeln!(html, "</section>");
+
+    comparison(&mut html, race_data, params, cars);
+    for (car, result) in cars {
+        car_section(&mut html, race_data, params, *car, result);
+    }
+    let _ = writeln!(html, "</body>\n</html>");
+    html
+}
+
+// Every car side by side: ranked by race time, then time, fuel and tyre wear against distance
+fn comparison(html: &mut String, race_data: &RaceData, params: &RaceParams, cars: &[(usize, Result<SolveOutcome, SolveError>)]) {
+    let _ = writeln!(html, "<section>\n<h2>Comparison</h2>");
+    let mut rows: Vec<(usize, Option<&PathInfo>, String)> = cars
+        .iter()
+        .map(|(car, result)| match result {
+            Ok(outcome) => (*car, outcome.best.as_ref(), outcome.status.to_string()),
+            Err(e) => (*car, None, e.to_string()),
+        })
+        .collect();
+    // Fastest first, cars without a strategy last in input order
+    rows.sort_by_key(|(car, best, _)| (best.map_or(Fixed::MAX, |p| p.total_time), *car));
+    let fastest = rows.first().and_then(|(_, best, _)| best.map(|p| p.total_time));
+
+    let _ = writeln!(
+        html,
+        "<table>\n<tr><th>rank</th><th>car</th><th>fuel</th><th>tyre cost</th><th>time</th><th>gap</th><th>pits</th><th>tyre penalty</th><th class=\"left\">status</th></tr>"
+    );
+    for (rank, (car, best, status)) in rows.iter().enumerate() {
+        let config = &race_data.cars[*car];
+        let name = format!("<td style=\"color:{}\"><b>{}</b></td>", car_color(*car), car + 1);
+        match best {
+            Some(p) => {
+                let tyre_penalty: Fixed = p.segments.iter().map(|s| s.tyre_penalty).sum();
+                let gap = fastest.map(|f| format!("+{}", p.total_time - f)).unwrap_or_default();
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td>{}<td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"left\">{}</td></tr>",
+                    rank + 1, name, config.fuel_capacity, config.tyre_cost, p.total_time, gap, p.pit_stops.len(), tyre_penalty, status
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>-</td>{}<td>{}</td><td>{}</td><td>-</td><td>-</td><td>-</td><td>-</td><td class=\"left error\">{}</td></tr>",
+                    name, config.fuel_capacity, config.tyre_cost, xml_escape(status)
+                );
+            }
+        }
+    }
+    let _ = writeln!(html, "</table>");
+
+    let profiles: Vec<(usize, Vec<ProfilePoint>)> = rows
+        .iter()
+        .filter_map(|(car, best, _)| best.map(|p| (*car, visual::race_profile(p, &race_data.cars[*car]))))
+        .collect();
+    if !profiles.is_empty() {
+        let series = |value: fn(&ProfilePoint) -> Fixed| -> Vec<Series> {
+            profiles
+                .iter()
+                .map(|(car, profile)| Series {
+                    name: format!("Car {}", car + 1),
+                    color: car_color(*car),
+                    points: profile.iter().map(|p| (p.distance.to_f64(), value(p).to_f64())).collect(),
+                })
+                .collect()
+        };
+        let _ = writeln!(html, "<div class=\"charts\">");
+        html.push_str(&line_chart("Race time", "distance", "time", &series(|p| p.time), &[], None));
+        html.push_str(&line_chart("Fuel", "distance", "fuel", &series(|p| p.fuel), &[], None));
+        html.push_str(&line_chart("Tyre wear", "distance", "wear", &series(|p| p.tyre_wear), &[], Some(params.tyre_wear_distance.to_f64())));
+        let _ = writeln!(html, "</div>");
+    }
+
+    let strategies: Vec<(usize, &PathInfo)> = rows.iter().filter_map(|(car, best, _)| best.map(|p| (*car, p))).collect();
+    if let Some(comparison) = compare_strategies(&strategies) {
+        strategy_comparison(html, &comparison);
+    }
+    let _ = writeln!(html, "</section>");
+}
+
+// The strategies lined up lap by lap against the fastest, the gap over the race and the segments
+// behind each car's gap
+fn strategy_comparison(html: &mut String, comparison: &Comparison) {
+    let reference = comparison.reference;
+    let _ = writeln!(html, "<h3>Lap by lap against car {}</h3>", reference + 1);
+    let _ = write!(html, "<table>\n<tr><th rowspan=\"2\">lap</th><th colspan=\"2\" style=\"color:{}\">car {}</th>", car_color(reference), reference + 1);
+    for car in &comparison.cars {
+        let _ = write!(html, "<th colspan=\"4\" style=\"color:{}\">car {}</th>", car_color(car.car), car.car + 1);
+    }
+    let _ = write!(html, "</tr>\n<tr><th>time</th><th class=\"left\">pits</th>");
+    for _ in &comparison.cars {
+        let _ = write!(html, "<th>time</th><th>delta</th><th>gap</th><th class=\"left\">pits</th>");
+    }
+    let _ = writeln!(html, "</tr>");
+    let laps = comparison.cars.first().map_or(0, |c| c.laps.len());
+    for lap in 0..laps {
+        let first = &comparison.cars[0].laps[lap];
+        let _ = write!(html, "<tr><td>{}</td><td>{}</td><td class=\"left\">{}</td>", lap + 1, first.reference_time, pit_list(&first.reference_pits));
+        for car in &comparison.cars {
+            let l = &car.laps[lap];
+            let pits = if l.pits_differ() { "left differ" } else { "left" };
+            let _ = write!(html, "<td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td>", l.time, signed(l.delta), signed(l.gap), pits, pit_list(&l.pits));
+        }
+        let _ = writeln!(html, "</tr>");
+    }
+    let _ = writeln!(html, "</table>");
+    if comparison.cars.iter().any(|c| c.laps.iter().any(|l| l.pits_differ())) {
+        let _ = writeln!(html, "<p class=\"muted\">Highlighted pit stops differ from car {}'s on that lap.</p>", reference + 1);
+    }
+
+    // The gap at each lap end, starting level
+    let mut series = vec![Series {
+        name: format!("Car {}", reference + 1),
+        color: car_color(reference),
+        points: (0..=laps).map(|lap| (lap as f64, 0.0)).collect(),
+    }];
+    for car in &comparison.cars {
+        let points = std::iter::once((0.0, 0.0)).chain(car.laps.iter().map(|l| ((l.lap + 1) as f64, l.gap.to_f64()))).collect();
+        series.push(Series { name: format!("Car {}", car.car + 1), color: car_color(car.car), points });
+    }
+    let title = format!("Gap to car {}", reference + 1);
+    html.push_str(&line_chart(&title, "lap", "gap", &series, &[], None));
+
+    for car in &comparison.cars {
+        if car.gap == Fixed::ZERO {
+            let _ = writeln!(html, "<h3>Where car {} ties with car {}</h3>", car.car + 1, reference + 1);
+        } else {
+            let _ = writeln!(html, "<h3>Where car {} loses {} to car {}</h3>", car.car + 1, car.gap, reference + 1);
+        }
+        if car.contributions.is_empty() {
+            let _ = writeln!(html, "<p class=\"muted\">Same times on every segment.</p>");
+            continue;
+        }
+        let _ = writeln!(html, "<table>\n<tr><th>lap</th><th class=\"left\">segment</th><th>delta</th><th class=\"left\">why</th></tr>");
+        for c in &car.contributions {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"left\">{} → {}</td><td>{}</td><td class=\"left\">{}</td></tr>",
+                c.lap + 1, c.from, c.to, signed(c.delta), xml_escape(&describe(c, car.car, reference))
+            );
+        }
+        let _ = writeln!(html, "</table>");
+    }
+}
+
+// One car: the summary `print_race_strategy` shows, its map, charts and lap by lap tables
+fn car_section(html: &mut String, race_data: &RaceData, params: &RaceParams, car: usize, result: &Result<SolveOutcome, SolveError>) {
+    let config = &race_data.cars[car];
+    let _ = writeln!(html, "<section id=\"car{}\">", car + 1);
+    let _ = writeln!(
+        html,
+        "<h2 style=\"color:{}\">Car {} race strategy</h2>\n<p class=\"muted\">Fuel capacity {}, tyre cost {}</p>",
+        car_color(car), car + 1, config.fuel_capacity, config.tyre_cost
+    );
+    let best = match result {
+        Ok(outcome) => match &outcome.best {
+            Some(best) => best,
+            None => {
+                let _ = writeln!(html, "<p class=\"error\">{}</p>\n</section>", SolveError::NoFeasibleStrategy);
+                return;
+            }
+        },
+        Err(e) => {
+            let _ = writeln!(html, "<p class=\"error\">{}</p>\n</section>", xml_escape(&e.to_string()));
+            return;
+        }
+    };
+
+    let _ = writeln!(html, "<p>Total race time: <b>{}</b> units</p>", best.total_time);
+    if best.pit_stops.is_empty() {
+        let _ = writeln!(html, "<p>Pit stops: none</p>");
+    } else {
+        let stops: Vec<String> = best.pit_stops.iter().map(|(lap, node)| format!("lap {} at node {}", lap + 1, node)).collect();
+        let _ = writeln!(html, "<p>Pit stops: {}</p>", stops.join(", "));
+    }
+    let path: Vec<String> = best
+        .node_sequence
+        .iter()
+        .map(|node| if race_data.pit_nodes.contains(node) { format!("[P{}]", node) } else { format!("[{}]", node) })
+        .collect();
+    let _ = writeln!(html, "<p>Race path: {}</p>", xml_escape(&path.join(" → ")));
+    html.push_str(&visual::circuit_svg(race_data, params.start_node, params.end_node, Some(best)));
+
+    // Charts with a line at every lap start and pit stop
+    let profile = visual::race_profile(best, config);
+    let mut markers: Vec<(f64, String)> = Vec::new();
+    for pair in profile.windows(2) {
+        if pair[1].lap != pair[0].lap {
+            markers.push((pair[0].distance.to_f64(), format!("L{}", pair[1].lap + 1)));
+        }
+    }
+    markers.extend(profile.iter().filter(|p| p.pit_stop).map(|p| (p.distance.to_f64(), "PIT".to_string())));
+    let series = |name: &str, value: fn(&ProfilePoint) -> Fixed| {
+        vec![Series { name: name.to_string(), color: car_color(car), points: profile.iter().map(|p| (p.distance.to_f64(), value(p).to_f64())).collect() }]
+    };
+    let _ = writeln!(html, "<div class=\"charts\">");
+    html.push_str(&line_chart("Fuel", "distance", "fuel", &series("fuel", |p| p.fuel), &markers, None));
+    html.push_str(&line_chart("Tyre wear", "distance", "wear", &series("tyre wear", |p| p.tyre_wear), &markers, Some(params.tyre_wear_distance.to_f64())));
+    let _ = writeln!(html, "</div>");
+
+    lap_tables(html, race_data, best);
+    let _ = writeln!(html, "</section>");
+}
+
+// A summary row per lap, then each lap's segments in a collapsible table
+fn lap_tables(html: &mut String, race_data: &RaceData, best: &PathInfo) {
+    let mut laps: Vec<u32> = best.segments.iter().map(|s| s.lap).collect();
+    laps.dedup();
+    let _ = writeln!(
+        html,
+        "<h3>Laps</h3>\n<table>\n<tr><th>lap</th><th>distance</th><th>time</th><th>pit stops</th><th>tyre penalty</th><th>fuel left</th><th>tyre wear</th></tr>"
+    );
+    for &lap in &laps {
+        let segments: Vec<_> = best.segments.iter().filter(|s| s.lap == lap).collect();
+        let last = segments.last().expect("every listed lap has a segment");
+        let distance: Fixed = segments.iter().map(|s| s.distance).sum();
+        let time: Fixed = segments.iter().map(|s| s.time).sum();
+        let penalty: Fixed = segments.iter().map(|s| s.tyre_penalty).sum();
+        let pits = segments.iter().filter(|s| s.pit_stop).count();
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            lap + 1, distance, time, pits, penalty, last.fuel_after, last.tyre_after
+        );
+    }
+    let _ = writeln!(html, "</table>");
+
+    for &lap in &laps {
+        let _ = writeln!(html, "<details>\n<summary>Lap {} segments</summary>", lap + 1);
+        let _ = writeln!(
+            html,
+            "<table>\n<tr><th class=\"left\">segment</th><th>distance</th><th>pit</th><th>time</th><th>cumulative</th><th>fuel</th><th>tyre wear</th><th>tyre penalty</th></tr>"
+        );
+        for s in best.segments.iter().filter(|s| s.lap == lap) {
+            let node = |n: usize| if race_data.pit_nodes.contains(&n) { format!("P{}", n) } else { n.to_string() };
+            let _ = writeln!(
+                html,
+                "<tr{}><td class=\"left\">{} → {}</td><td>{}</td><td>{}</td><td>+{}</td><td>{}</td><td>{} → {}</td><td>{} → {}</td><td>{}</td></tr>",
+                if s.pit_stop { " class=\"pit\"" } else { "" },
+                node(s.from), node(s.to), s.distance, if s.pit_stop { "PIT" } else { "" }, s.time, s.cumulative_time,
+                s.fuel_before, s.fuel_after, s.tyre_before, s.tyre_after, s.tyre_penalty
+            );
+        }
+        let _ = writeln!(html, "</table>\n</details>");
+    }
+}
+
+// One line on a chart
+pub struct Series {
+    pub name: String,
+    pub color: &'static str,
+    pub points: Vec<(f64, f64)>,
+}
+
+const CHART_WIDTH: f64 = 480.0;
+const CHART_HEIGHT: f64 = 220.0;
+const LEFT: f64 = 50.0;
+const RIGHT: f64 = 12.0;
+const TOP: f64 = 28.0;
+const BOTTOM: f64 = 34.0;
+
+// An inline SVG line chart against `x_label` (race distance, laps), with labelled vertical
+// `markers` (lap starts, pit stops) and an optional dashed horizontal `threshold`. The y axis
+// reaches below zero when a value does; whole-numbered x values get whole-numbered ticks.
+pub fn line_chart(title: &str, x_label: &str, y_label: &str, series: &[Series], markers: &[(f64, String)], threshold: Option<f64>) -> String {
+    let all = series.iter().flat_map(|s| s.points.iter());
+    let max_x = all.clone().map(|p| p.0).fold(0.0, f64::max);
+    let max_y = all.clone().map(|p| p.1).chain(threshold).fold(0.0, f64::max);
+    let min_y = all.clone().map(|p| p.1).fold(0.0, f64::min);
+    let (mut x_step, _) = axis(max_x);
+    if all.clone().all(|p| p.0.fract() == 0.0) {
+        x_step = x_step.max(1.0);
+    }
+    let x_top = ((max_x / x_step).ceil() * x_step).max(x_step);
+    let (y_step, _) = axis(max_y - min_y);
+    let y_bottom = (min_y / y_step).floor() * y_step;
+    let y_top = ((max_y / y_step).ceil() * y_step).max(y_bottom + y_step);
+    let plot_w = CHART_WIDTH - LEFT - RIGHT;
+    let plot_h = CHART_HEIGHT - TOP - BOTTOM;
+    let sx = |x: f64| LEFT + x / x_top * plot_w;
+    let sy = |y: f64| TOP + plot_h - (y - y_bottom) / (y_top - y_bottom) * plot_h;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="Helvetica, Arial, sans-serif" font-size="10">"#,
+        w = CHART_WIDTH, h = CHART_HEIGHT
+    );
+    let _ = writeln!(svg, r#"<text x="{}" y="16" font-size="13" font-weight="bold">{}</text>"#, LEFT, xml_escape(title));
+    // Grid and tick labels
+    let mut y = y_bottom;
+    while y <= y_top + y_step / 2.0 {
+        let _ = writeln!(svg, r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#e4e4e4"/>"##, LEFT, sy(y), LEFT + plot_w, sy(y));
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#, LEFT - 4.0, sy(y) + 3.0, Fixed::from_f64(y));
+        y += y_step;
+    }
+    let mut x = 0.0;
+    while x <= x_top + x_step / 2.0 {
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#, sx(x), TOP + plot_h + 13.0, Fixed::from_f64(x));
+        x += x_step;
+    }
+    let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#, LEFT + plot_w / 2.0, CHART_HEIGHT - 4.0, xml_escape(x_label));
+    let _ = writeln!(svg, r#"<text x="12" y="{:.1}" text-anchor="middle" transform="rotate(-90 12 {:.1})">{}</text>"#, TOP + plot_h / 2.0, TOP + plot_h / 2.0, xml_escape(y_label));
+    let _ = writeln!(svg, r##"<rect x="{}" y="{}" width="{:.1}" height="{:.1}" fill="none" stroke="#999"/>"##, LEFT, TOP, plot_w, plot_h);
+
+    if y_bottom < 0.0 {
+        let _ = writeln!(svg, r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#999"/>"##, LEFT, sy(0.0), LEFT + plot_w, sy(0.0));
+    }
+    if let Some(limit) = threshold {
+        let _ = writeln!(svg, r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#c62828" stroke-dasharray="5,3"/>"##, LEFT, sy(limit), LEFT + plot_w, sy(limit));
+    }
+    for (at, label) in markers {
+        let _ = writeln!(svg, r##"<line x1="{:.1}" y1="{}" x2="{:.1}" y2="{:.1}" stroke="#888" stroke-dasharray="2,3"/>"##, sx(*at), TOP, sx(*at), TOP + plot_h);
+        let _ = writeln!(svg, r##"<text x="{:.1}" y="{:.1}" fill="#555">{}</text>"##, sx(*at) + 2.0, TOP + 9.0, xml_escape(label));
+    }
+    for s in series {
+        let points: Vec<String> = s.points.iter().map(|&(x, y)| format!("{:.1},{:.1}", sx(x), sy(y))).collect();
+        let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#, points.join(" "), s.color);
+    }
+    // Legend along the top right
+    for (k, s) in series.iter().enumerate().rev() {
+        let x = CHART_WIDTH - RIGHT - 70.0 * (series.len() - k) as f64;
+        let _ = writeln!(svg, r#"<rect x="{:.1}" y="10" width="12" height="3" fill="{}"/>"#, x, s.color);
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="15">{}</text>"#, x + 15.0, xml_escape(&s.name));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Tick step and axis end for values up to `max`: about five round-numbered ticks
+fn axis(max: f64) -> (f64, f64) {
+    if max <= 0.0 {
+        return (1.0, 1.0);
+    }
+    let rough = max / 5.0;
+    let power = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].into_iter().map(|m| m * power).find(|&s| s >= rough).unwrap_or(10.0 * power);
+    (step, (max / step).ceil() * step)
+}
@@ -0,0 +1,625 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use colored::*;
+use crate::graph_rcspp::PathInfo;
+use crate::input_parser::RaceData;
+use crate::units::Fixed;
+use crate::visual::{pit_lane_edges, LAP_COLORS};
+
+// Main lines longer than this are folded when `MapOptions::collapse` is left to decide
+pub const FOLD_ABOVE: usize = 30;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MapOptions {
+    // Columns per page; longer main lines continue on further pages
+    pub width: usize,
+    // Fold runs of plain nodes into one dashed stretch; `None` folds only beyond `FOLD_ABOVE` nodes
+    pub collapse: Option<bool>,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        MapOptions { width: terminal_width(), collapse: None }
+    }
+}
+
+// The terminal's width from `COLUMNS`, or 100 columns
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).filter(|&c| c >= 40).unwrap_or(100)
+}
+
+// Terminal colour of a lap, matching `LAP_COLORS` in the DOT exports
+pub fn lap_color(lap: u32) -> Color {
+    match LAP_COLORS[lap as usize % LAP_COLORS.len()] {
+        "blue" => Color::Blue,
+        "green" => Color::Green,
+        "red" => Color::Red,
+        "orange" => Color::Yellow,
+        _ => Color::Magenta,
+    }
+}
+
+// Order in which the main line is drawn: from the start, always taking the next node along rather
+// than a shortcut, i.e. skipping any successor that another successor leads to within a few hops.
+// Pit lane nodes are left out; they are drawn as detours below the line.
+pub fn main_line(adj: &[Vec<(usize, Fixed)>], start: usize, skip: &HashSet<usize>) -> Vec<usize> {
+    let n = adj.len();
+    let mut visited = vec![false; n];
+    let mut line = Vec::new();
+    let mut current = (start < n).then_some(start);
+    while let Some(node) = current {
+        visited[node] = true;
+        line.push(node);
+        let candidates: Vec<(usize, Fixed)> = adj[node].iter().copied().filter(|&(v, _)| !visited[v] && !skip.contains(&v)).collect();
+        let reached_via_other = |target: usize| {
+            candidates.iter().any(|&(other, _)| other != target && within_hops(adj, other, target, 6, &visited, skip))
+        };
+        let next_along: Vec<(usize, Fixed)> = candidates.iter().copied().filter(|&(v, _)| !reached_via_other(v)).collect();
+        let pool = if next_along.is_empty() { &candidates } else { &next_along };
+        current = pool.iter().min_by_key(|&&(v, d)| (d, v)).map(|&(v, _)| v);
+    }
+    line
+}
+
+// Whether `to` is reachable from `from` in at most `hops` edges through unvisited, unskipped nodes
+fn within_hops(adj: &[Vec<(usize, Fixed)>], from: usize, to: usize, hops: usize, visited: &[bool], skip: &HashSet<usize>) -> bool {
+    let mut seen = HashSet::from([from]);
+    let mut queue = VecDeque::from([(from, 0)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        if node == to {
+            return true;
+        }
+        if depth == hops {
+            continue;
+        }
+        for &(v, _) in &adj[node] {
+            if !visited[v] && !skip.contains(&v) && seen.insert(v) {
+                queue.push_back((v, depth + 1));
+            }
+        }
+    }
+    false
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Style {
+    heavy: bool,
+    dashed: bool,
+    color: Option<Color>,
+    bold: bool,
+}
+
+const PLAIN: Style = Style { heavy: false, dashed: false, color: None, bold: false };
+
+#[derive(Debug, Clone, Copy)]
+enum Cell {
+    Empty,
+    Line(u8, Style),
+    Text(char, Style),
+}
+
+// A grid of box-drawing lines and text; lines crossing in a cell join up
+struct Canvas {
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Canvas {
+    fn new(height: usize) -> Self {
+        Canvas { rows: vec![Vec::new(); height] }
+    }
+
+    fn cell(&mut self, x: usize, y: usize) -> &mut Cell {
+        let row = &mut self.rows[y];
+        if row.len() <= x {
+            row.resize(x + 1, Cell::Empty);
+        }
+        &mut row[x]
+    }
+
+    fn line(&mut self, x: usize, y: usize, mask: u8, style: Style) {
+        let cell = self.cell(x, y);
+        *cell = match *cell {
+            Cell::Empty => Cell::Line(mask, style),
+            // The strategy's lines win over the rest of the track
+            Cell::Line(old, old_style) => Cell::Line(old | mask, if style.heavy || !old_style.heavy { style } else { old_style }),
+            text => text,
+        };
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        for (i, ch) in text.chars().enumerate() {
+            *self.cell(x + i, y) = Cell::Text(ch, style);
+        }
+    }
+
+    fn hline(&mut self, x1: usize, x2: usize, y: usize, style: Style) {
+        for x in x1..=x2 {
+            self.line(x, y, LEFT | RIGHT, style);
+        }
+    }
+
+    fn vline(&mut self, x: usize, y1: usize, y2: usize, style: Style) {
+        for y in y1.min(y2)..=y1.max(y2) {
+            self.line(x, y, UP | DOWN, style);
+        }
+    }
+
+    fn render(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let mut out = String::new();
+                for cell in row {
+                    let (ch, style) = match *cell {
+                        Cell::Empty => (' ', PLAIN),
+                        Cell::Line(mask, style) => (box_char(mask, style), style),
+                        Cell::Text(ch, style) => (ch, style),
+                    };
+                    let mut piece = ch.to_string().normal();
+                    if let Some(color) = style.color {
+                        piece = piece.color(color);
+                    }
+                    if style.bold {
+                        piece = piece.bold();
+                    }
+                    out.push_str(&piece.to_string());
+                }
+                out.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+fn box_char(mask: u8, style: Style) -> char {
+    let (light, heavy) = match mask {
+        m if m == LEFT | RIGHT || m == LEFT || m == RIGHT => {
+            if style.dashed {
+                ('┄', '┅')
+            } else {
+                ('─', '━')
+            }
+        }
+        m if m == UP | DOWN || m == UP || m == DOWN => ('│', '┃'),
+        m if m == DOWN | RIGHT => ('╭', '┏'),
+        m if m == DOWN | LEFT => ('╮', '┓'),
+        m if m == UP | RIGHT => ('╰', '┗'),
+        m if m == UP | LEFT => ('╯', '┛'),
+        m if m == UP | DOWN | RIGHT => ('├', '┣'),
+        m if m == UP | DOWN | LEFT => ('┤', '┫'),
+        m if m == LEFT | RIGHT | DOWN => ('┬', '┳'),
+        m if m == LEFT | RIGHT | UP => ('┴', '┻'),
+        _ => ('┼', '╋'),
+    };
+    if style.heavy { heavy } else { light }
+}
+
+// How consecutive nodes on the main line are joined
+#[derive(Debug, Clone)]
+enum Link {
+    // Drawn at least `width` columns wide, so a pit lane under it has room for its label
+    Edge { distance: Fixed, style: Style, width: usize },
+    // Folded nodes between two kept ones
+    Folded { nodes: usize, distance: Fixed, style: Style },
+    // No edge from one to the next
+    Gap,
+}
+
+impl Link {
+    fn text(&self) -> String {
+        match self {
+            Link::Edge { distance, style, width } => {
+                let bar = if style.heavy { "━" } else { "─" };
+                let label = distance.to_string();
+                let bars = width.saturating_sub(label.len() + 1).max(2);
+                format!("{}{}{}▶", bar.repeat(bars / 2), label, bar.repeat(bars - bars / 2))
+            }
+            Link::Folded { nodes, distance, style } => {
+                let dash = if style.heavy { '┅' } else { '┄' };
+                format!("{}{} nodes, {}{}▶", dash, nodes, distance, dash)
+            }
+            Link::Gap => "   ".to_string(),
+        }
+    }
+}
+
+// An edge drawn as an arc above (forward) or below (back, or a pit lane detour) the main line
+#[derive(Debug, Clone)]
+struct Arc {
+    from: usize,
+    to: usize,
+    label: String,
+    above: bool,
+    style: Style,
+    // For a pit lane, its pit node and the style of the half out of it; `style` is the half in
+    lane: Option<(usize, Style)>,
+}
+
+// Where one end of an arc is on a page
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum End {
+    Source,
+    Target,
+    // On another page
+    Off,
+}
+
+struct PageArc<'a> {
+    arc: &'a Arc,
+    x1: usize,
+    x2: usize,
+    left: End,
+    right: End,
+    level: usize,
+}
+
+impl PageArc<'_> {
+    // Column where the arc's label starts, when it fits between the ends
+    fn label_x(&self) -> Option<usize> {
+        let len = self.arc.label.chars().count() + 2;
+        (self.x2 - self.x1 > len + 2).then(|| self.x1 + (self.x2 - self.x1 - len) / 2 + 1)
+    }
+
+    // Column of a pit lane's `[Pn]` box, or the middle of the lane when its label does not fit
+    fn pit_x(&self) -> usize {
+        let label = &self.arc.label;
+        let box_at = label.chars().position(|c| c == '[').unwrap_or(0);
+        let box_len = label.chars().skip(box_at).take_while(|&c| c != ']').count() + 1;
+        self.label_x().map_or((self.x1 + self.x2) / 2, |x| x + 1 + box_at + box_len / 2)
+    }
+}
+
+// Where `arc` lies on page `p`, given the columns of the nodes drawn on it; ends on other pages
+// run to the margin or the right edge. `None` when neither end is on the page.
+fn place<'a>(arc: &'a Arc, column: impl Fn(usize) -> Option<usize>, page_of: &HashMap<usize, usize>, p: usize, margin: usize, right_edge: usize) -> Option<PageArc<'a>> {
+    let from = column(arc.from).map(|c| (c, End::Source));
+    let to = column(arc.to).map(|c| (c, End::Target));
+    let (x1, left, x2, right) = match (from, to) {
+        (None, None) => return None,
+        (Some((a, ra)), Some((b, rb))) => if a <= b { (a, ra, b, rb) } else { (b, rb, a, ra) },
+        // The other end is on an earlier or a later page
+        (Some((c, role)), None) | (None, Some((c, role))) => {
+            let other = if from.is_some() { arc.to } else { arc.from };
+            if page_of.get(&other).copied().unwrap_or(0) < p { (margin / 2, End::Off, c, role) } else { (c, role, right_edge, End::Off) }
+        }
+    };
+    Some(PageArc { arc, x1, x2, left, right, level: 0 })
+}
+
+// Gives each arc the first level on its side, from the levels added by this call, where it
+// overlaps no other; narrow arcs go first so they sit closest to the main line
+fn stack(page_arcs: &mut [PageArc], levels: &mut [Vec<Vec<(usize, usize)>>; 2]) {
+    let first = [levels[0].len(), levels[1].len()];
+    page_arcs.sort_by_key(|a| (a.x2 - a.x1, a.x1));
+    for a in page_arcs {
+        let side_index = usize::from(!a.arc.above);
+        let side = &mut levels[side_index];
+        let free = |taken: &Vec<(usize, usize)>| taken.iter().all(|&(l, r)| a.x2 + 1 < l || r + 1 < a.x1);
+        a.level = match side[first[side_index]..].iter().position(free) {
+            Some(level) => first[side_index] + level,
+            None => {
+                side.push(Vec::new());
+                side.len() - 1
+            }
+        };
+        side[a.level].push((a.x1, a.x2));
+    }
+}
+
+// The track as a box-drawing diagram, one page of lines after another. The main line runs left to
+// right from `start`; shortcuts arc over it, back edges such as the one closing the lap arc under
+// it, and pit lanes are detours under it through their `[Pn]` box. Any other edge into or out of a
+// lane's pit arcs under the lane to its box. With a strategy, the edges it drives are heavy and in
+// their lap's colour and pit stops taken are marked `*`.
+pub fn track_map(race_data: &RaceData, adj: &[Vec<(usize, Fixed)>], start: usize, end: usize, strategy: Option<&PathInfo>, options: &MapOptions) -> Vec<Vec<String>> {
+    let n = adj.len();
+    // Pit lanes: pit nodes bypassed by a direct edge, between nodes of the main line
+    let lane_edges = pit_lane_edges(race_data);
+    let mut lanes: Vec<(usize, usize, usize)> = Vec::new();
+    for &pit in &race_data.pit_nodes {
+        let into = lane_edges.iter().find(|&&(_, v)| v == pit).map(|&(u, _)| u);
+        let out = lane_edges.iter().find(|&&(u, _)| u == pit).map(|&(_, v)| v);
+        if let (Some(a), Some(b)) = (into, out)
+            && pit != start
+            && pit != end
+            && !race_data.pit_nodes.contains(&a)
+            && !race_data.pit_nodes.contains(&b)
+        {
+            lanes.push((a, pit, b));
+        }
+    }
+    let lane_pits: HashSet<usize> = lanes.iter().map(|&(_, p, _)| p).collect();
+
+    let mut line = main_line(adj, start, &lane_pits);
+    let on_line: HashSet<usize> = line.iter().copied().collect();
+    line.extend((0..n).filter(|v| !on_line.contains(v) && !lane_pits.contains(v)));
+    let position: HashMap<usize, usize> = line.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+    // What the strategy drove, on its first lap over each edge, and where it pitted
+    let mut driven: HashMap<(usize, usize), u32> = HashMap::new();
+    let mut pitted = HashSet::new();
+    for segment in strategy.map_or(&[][..], |s| &s.segments[..]) {
+        driven.entry((segment.from, segment.to)).or_insert(segment.lap);
+        if segment.pit_stop {
+            pitted.insert(segment.from);
+        }
+    }
+    let style_of = |edges: &[(usize, usize)]| match edges.iter().map(|e| driven.get(e)).collect::<Option<Vec<_>>>() {
+        Some(laps) if !laps.is_empty() => Style { heavy: true, dashed: false, color: Some(lap_color(*laps[0])), bold: false },
+        _ => PLAIN,
+    };
+
+    // Main line links, then every other edge as an arc
+    let mut links: Vec<Option<(Fixed, (usize, usize))>> = vec![None; line.len().saturating_sub(1)];
+    let mut arcs = Vec::new();
+    for (u, neighbors) in adj.iter().enumerate() {
+        for &(v, distance) in neighbors {
+            if lanes.iter().any(|&(a, p, b)| (u, v) == (a, p) || (u, v) == (p, b)) {
+                continue;
+            }
+            let arc = |above: bool| Arc { from: u, to: v, label: distance.to_string(), above, style: style_of(&[(u, v)]), lane: None };
+            if lane_pits.contains(&u) || lane_pits.contains(&v) {
+                arcs.push(arc(false));
+                continue;
+            }
+            let (Some(&pu), Some(&pv)) = (position.get(&u), position.get(&v)) else { continue };
+            if pv == pu + 1 && links[pu].is_none() {
+                links[pu] = Some((distance, (u, v)));
+            } else {
+                arcs.push(arc(pv > pu));
+            }
+        }
+    }
+    let lane_label = |a: usize, pit: usize, b: usize| {
+        let distance = |u: usize, v: usize| adj[u].iter().find(|&&(w, _)| w == v).map_or(Fixed::ZERO, |&(_, d)| d);
+        let mark = if pitted.contains(&pit) { " *" } else { "" };
+        format!("{} [P{}{}] {}", distance(a, pit), pit, mark, distance(pit, b))
+    };
+    for &(a, pit, b) in &lanes {
+        arcs.push(Arc {
+            from: a,
+            to: b,
+            label: lane_label(a, pit, b),
+            above: false,
+            style: style_of(&[(a, pit)]),
+            lane: Some((pit, style_of(&[(pit, b)]))),
+        });
+    }
+
+    // Fold runs of plain nodes: not the start or finish, no pit, no arc, on an unbroken stretch
+    let collapse = options.collapse.unwrap_or(line.len() > FOLD_ABOVE);
+    let arc_ends: HashSet<usize> = arcs.iter().flat_map(|a| [a.from, a.to]).collect();
+    let plain = |i: usize| {
+        let v = line[i];
+        i > 0 && i + 1 < line.len() && v != start && v != end && !race_data.pit_nodes.contains(&v) && !arc_ends.contains(&v)
+            && links[i - 1].is_some() && links[i].is_some()
+    };
+    let mut kept: Vec<usize> = Vec::new();
+    let mut item_links: Vec<Link> = Vec::new();
+    let mut i = 0;
+    while i < line.len() {
+        kept.push(i);
+        if i + 1 == line.len() {
+            break;
+        }
+        let mut j = i + 1;
+        while collapse && plain(j) {
+            j += 1;
+        }
+        // Folding a single node saves nothing
+        if j - i < 3 {
+            j = i + 1;
+        }
+        let edges: Vec<(usize, usize)> = (i..j).filter_map(|k| links[k].map(|(_, e)| e)).collect();
+        let link = if edges.len() < j - i {
+            Link::Gap
+        } else if j == i + 1 {
+            let width = lanes
+                .iter()
+                .filter(|&&(a, _, b)| a == line[i] && b == line[i + 1])
+                .map(|&(a, pit, b)| lane_label(a, pit, b).chars().count() + 4)
+                .max()
+                .unwrap_or(0);
+            Link::Edge { distance: links[i].map_or(Fixed::ZERO, |(d, _)| d), style: style_of(&edges), width }
+        } else {
+            let distance = (i..j).filter_map(|k| links[k].map(|(d, _)| d)).sum();
+            Link::Folded { nodes: j - i - 1, distance, style: Style { dashed: true, ..style_of(&edges) } }
+        };
+        item_links.push(link);
+        i = j;
+    }
+
+    let label = |v: usize| {
+        let mut text = if race_data.pit_nodes.contains(&v) { format!("[P{}", v) } else { format!("[{}", v) };
+        match (v == start, v == end) {
+            (true, true) => text.push_str(" S/F"),
+            (true, false) => text.push_str(" S"),
+            (false, true) => text.push_str(" F"),
+            _ => {}
+        }
+        if pitted.contains(&v) {
+            text.push_str(" *");
+        }
+        text.push(']');
+        text
+    };
+
+    // Split the kept nodes into pages of at most `options.width` columns
+    let margin = 8;
+    let mut pages: Vec<Vec<usize>> = vec![Vec::new()];
+    let mut x = 0;
+    for (k, &i) in kept.iter().enumerate() {
+        let width = label(line[i]).chars().count() + item_links.get(k).map_or(0, |l| l.text().chars().count());
+        if x > 0 && x + width + 2 * margin > options.width {
+            pages.push(Vec::new());
+            x = 0;
+        }
+        pages.last_mut().expect("always one page").push(k);
+        x += width;
+    }
+    let paged = pages.len() > 1;
+    let mut page_of: HashMap<usize, usize> = HashMap::new();
+    for (p, items) in pages.iter().enumerate() {
+        for &k in items {
+            page_of.insert(line[kept[k]], p);
+        }
+    }
+    for &(a, pit, _) in &lanes {
+        if let Some(&p) = page_of.get(&a) {
+            page_of.insert(pit, p);
+        }
+    }
+
+    pages
+        .iter()
+        .enumerate()
+        .map(|(p, items)| {
+            // Columns of each node on this page
+            let mut x = if paged { margin } else { 0 };
+            let mut centers: HashMap<usize, usize> = HashMap::new();
+            let mut row: Vec<(usize, String, Style)> = Vec::new();
+            if paged && p > 0 {
+                row.push((x - 2, "…".to_string(), PLAIN));
+            }
+            for &k in items {
+                let v = line[kept[k]];
+                let text = label(v);
+                let style = Style {
+                    color: if race_data.pit_nodes.contains(&v) { Some(Color::Red) } else { None },
+                    bold: v == start || v == end || pitted.contains(&v),
+                    ..PLAIN
+                };
+                centers.insert(v, x + text.chars().count() / 2);
+                let width = text.chars().count();
+                row.push((x, text, style));
+                x += width;
+                if let Some(link) = item_links.get(k) {
+                    let style = match link {
+                        Link::Edge { style, .. } | Link::Folded { style, .. } => *style,
+                        Link::Gap => PLAIN,
+                    };
+                    let text = link.text();
+                    let width = text.chars().count();
+                    row.push((x, text, style));
+                    x += width;
+                }
+            }
+            if paged && p + 1 < pages.len() {
+                row.push((x, "…".to_string(), PLAIN));
+            }
+            let right_edge = x + 1;
+
+            // Arcs with at least one end on this page, stacked so none overlap: pit lanes right under
+            // the line, then a free row for the arrows into their boxes, the arcs to lane pits and the rest
+            let to_lane_pit = |arc: &Arc| lane_pits.contains(&arc.from) || lane_pits.contains(&arc.to);
+            let mut levels: [Vec<Vec<(usize, usize)>>; 2] = [Vec::new(), Vec::new()];
+            let mut page_arcs: Vec<PageArc> = arcs
+                .iter()
+                .filter(|arc| arc.lane.is_some())
+                .filter_map(|arc| place(arc, |v| centers.get(&v).copied(), &page_of, p, margin, right_edge))
+                .collect();
+            stack(&mut page_arcs, &mut levels);
+            let pit_boxes: HashMap<usize, (usize, usize)> =
+                page_arcs.iter().filter_map(|a| a.arc.lane.map(|(pit, _)| (pit, (a.pit_x(), a.level)))).collect();
+            let column = |v: usize| centers.get(&v).or(pit_boxes.get(&v).map(|(x, _)| x)).copied();
+            let mut pit_arcs: Vec<PageArc> =
+                arcs.iter().filter(|arc| to_lane_pit(arc)).filter_map(|arc| place(arc, column, &page_of, p, margin, right_edge)).collect();
+            let arrow_level = levels[1].len();
+            if !pit_arcs.is_empty() {
+                levels[1].push(Vec::new());
+            }
+            stack(&mut pit_arcs, &mut levels);
+            let mut rest: Vec<PageArc> = arcs
+                .iter()
+                .filter(|arc| arc.lane.is_none() && !to_lane_pit(arc))
+                .filter_map(|arc| place(arc, |v| centers.get(&v).copied(), &page_of, p, margin, right_edge))
+                .collect();
+            stack(&mut rest, &mut levels);
+            page_arcs.extend(pit_arcs);
+            page_arcs.extend(rest);
+
+            // Rows: arcs above, stems, the main line, stems, arcs below
+            let above = levels[0].len();
+            let below = levels[1].len();
+            let node_row = above + 1;
+            let mut canvas = Canvas::new(above + below + 3);
+            for (x, text, style) in &row {
+                canvas.text(*x, node_row, text, *style);
+            }
+            for a in &page_arcs {
+                let (row, stem, toward): (usize, usize, u8) =
+                    if a.arc.above { (above - 1 - a.level, node_row - 1, DOWN) } else { (node_row + 2 + a.level, node_row + 1, UP) };
+                // A pit lane's halves are styled apart, split at its box
+                let from_on_left = a.left == End::Source || a.right == End::Target;
+                let (split, left_style, right_style) = match a.arc.lane {
+                    Some((_, out)) if from_on_left => (a.pit_x(), a.arc.style, out),
+                    Some((_, out)) => (a.pit_x(), out, a.arc.style),
+                    None => (a.x2, a.arc.style, a.arc.style),
+                };
+                for (x, end, side, style) in [(a.x1, a.left, RIGHT, left_style), (a.x2, a.right, LEFT, right_style)] {
+                    match end {
+                        End::Off => canvas.line(x, row, LEFT | RIGHT, style),
+                        End::Source | End::Target => {
+                            // Arcs to a lane's pit run up to its box, with their arrow in the free row
+                            let node = if end == End::Source { a.arc.from } else { a.arc.to };
+                            let (top, stem) = match pit_boxes.get(&node) {
+                                Some(&(_, level)) => (node_row + 3 + level, node_row + 2 + arrow_level),
+                                None => (stem, stem),
+                            };
+                            canvas.line(x, row, toward | side, style);
+                            let (from, to) = if row < stem { (row + 1, stem) } else { (top, row - 1) };
+                            if from <= to {
+                                canvas.vline(x, from, to, style);
+                            }
+                            if end == End::Target {
+                                canvas.text(x, stem, if a.arc.above { "▼" } else { "▲" }, Style { heavy: false, dashed: false, ..style });
+                            }
+                        }
+                    }
+                }
+                let split = split.max(a.x1 + 1).min(a.x2);
+                if split > a.x1 + 1 {
+                    canvas.hline(a.x1 + 1, split - 1, row, left_style);
+                }
+                if a.x2 > split {
+                    canvas.hline(split, a.x2 - 1, row, right_style);
+                }
+                // Label on the arc when it fits, a lane's box in red like the pit nodes on the line
+                if let Some(x) = a.label_x() {
+                    let text = format!(" {} ", a.arc.label);
+                    let box_start = text.find('[').unwrap_or(text.len());
+                    let box_end = text.find(']').map_or(box_start, |i| i + 1);
+                    let pit_style = Style { color: Some(Color::Red), bold: a.arc.lane.is_some_and(|(pit, _)| pitted.contains(&pit)), ..PLAIN };
+                    for (i, ch) in text.chars().enumerate() {
+                        let style = match a.arc.lane {
+                            Some(_) if (box_start..box_end).contains(&i) => pit_style,
+                            _ if x + i < split => left_style,
+                            _ => right_style,
+                        };
+                        canvas.text(x + i, row, &ch.to_string(), style);
+                    }
+                }
+                // The node at the end that is on another page, and which way the arc runs
+                let here_is_source = column(a.arc.from).is_some();
+                let other = if here_is_source { a.arc.to } else { a.arc.from };
+                if a.left == End::Off {
+                    let tag = if here_is_source { format!("[{}]◀", other) } else { format!("[{}]", other) };
+                    canvas.text(a.x1.saturating_sub(tag.chars().count()), row, &tag, left_style);
+                }
+                if a.right == End::Off {
+                    let tag = if here_is_source { format!("▶[{}]", other) } else { format!("[{}]", other) };
+                    canvas.text(a.x2 + 1, row, &tag, right_style);
+                }
+            }
+            let mut lines = canvas.render();
+            while lines.last().is_some_and(|l| l.is_empty()) {
+                lines.pop();
+            }
+            let blank = lines.iter().take_while(|l| l.is_empty()).count();
+            lines.split_off(blank)
+        })
+        .collect()
+}
@@ -0,0 +1,360 @@
+use std::io;
+use std::time::{Duration, Instant};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Gauge, List, ListItem, ListState, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use crate::graph_rcspp::{self, Graph, PathInfo, RaceParams, Resolution, Segment, SolveError, SolveOptions, SolveOutcome};
+use crate::input_parser::RaceData;
+use crate::track_map::{track_map, MapOptions};
+use crate::units::Fixed;
+
+// A race setting that can be changed from the keyboard, and re-solved for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Laps,
+    PitPenalty,
+    TyreWear,
+}
+
+impl Param {
+    const ALL: [Param; 3] = [Param::Laps, Param::PitPenalty, Param::TyreWear];
+
+    fn name(self) -> &'static str {
+        match self {
+            Param::Laps => "Laps",
+            Param::PitPenalty => "Pit penalty",
+            Param::TyreWear => "Tyre wear",
+        }
+    }
+}
+
+// Everything the terminal UI shows; key presses change it, `draw` renders it
+pub struct App {
+    pub scenario: String,
+    pub race_data: RaceData,
+    graph: Graph,
+    adj: Vec<Vec<(usize, Fixed)>>,
+    // 0-based indices of the cars shown
+    pub cars: Vec<usize>,
+    // Laps, pit penalty and tyre wear as edited; the resolution is used unless `buckets` is set
+    pub params: RaceParams,
+    buckets: Option<u32>,
+    options: SolveOptions,
+    // One per shown car
+    pub results: Vec<Result<SolveOutcome, SolveError>>,
+    // Index into `cars`
+    pub selected: usize,
+    // Segments of the selected car's strategy driven so far
+    pub position: usize,
+    pub param: Param,
+    pub playing: bool,
+    pub status: String,
+}
+
+impl App {
+    // Solves every car once, ready to show the first one at the start line
+    pub fn new(scenario: String, race_data: RaceData, cars: Vec<usize>, params: RaceParams, buckets: Option<u32>, options: SolveOptions) -> Self {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let adj = race_data.adjacency();
+        let mut app = App {
+            scenario,
+            race_data,
+            graph,
+            adj,
+            cars,
+            params,
+            buckets,
+            options,
+            results: Vec::new(),
+            selected: 0,
+            position: 0,
+            param: Param::Laps,
+            playing: false,
+            status: String::new(),
+        };
+        app.solve();
+        app
+    }
+
+    // Solves every shown car with the current parameters and rewinds to the start
+    pub fn solve(&mut self) {
+        let started = Instant::now();
+        let selected = RaceData {
+            c: self.cars.len(),
+            cars: self.cars.iter().map(|&i| self.race_data.cars[i].clone()).collect(),
+            ..self.race_data.clone()
+        };
+        let (params, buckets) = (self.params, self.buckets);
+        self.results = graph_rcspp::solve_all_with(&selected, &self.graph, &self.options, |car| RaceParams {
+            resolution: buckets.map_or(params.resolution, |b| Resolution::buckets(car, b)),
+            ..params
+        });
+        self.position = 0;
+        self.playing = false;
+        self.status = format!("Solved {} car(s) in {:.0?}", self.cars.len(), started.elapsed());
+    }
+
+    // The selected car's strategy, if it has one
+    pub fn strategy(&self) -> Option<&PathInfo> {
+        self.results.get(self.selected)?.as_ref().ok()?.best.as_ref()
+    }
+
+    fn segments(&self) -> &[Segment] {
+        self.strategy().map_or(&[], |s| &s.segments)
+    }
+
+    // Lap of the segment about to be driven, or of the last one at the finish
+    pub fn lap(&self) -> u32 {
+        let segments = self.segments();
+        segments.get(self.position).or(segments.last()).map_or(0, |s| s.lap)
+    }
+
+    // Positions where a lap ends: the finish, and wherever the next segment is on a later lap
+    fn lap_ends(&self) -> Vec<usize> {
+        let segments = self.segments();
+        (1..=segments.len()).filter(|&k| k == segments.len() || segments[k].lap != segments[k - 1].lap).collect()
+    }
+
+    pub fn next_lap(&mut self) {
+        self.position = self.lap_ends().into_iter().find(|&k| k > self.position).unwrap_or(self.position);
+    }
+
+    pub fn previous_lap(&mut self) {
+        self.position = self.lap_ends().into_iter().rev().find(|&k| k < self.position).unwrap_or(0);
+    }
+
+    // One segment on; stops playing at the finish
+    pub fn tick(&mut self) {
+        if self.position < self.segments().len() {
+            self.position += 1;
+        }
+        if self.position == self.segments().len() {
+            self.playing = false;
+        }
+    }
+
+    // Nudges the selected parameter one step up or down and re-solves
+    pub fn adjust(&mut self, up: bool) {
+        let step = |value: Fixed, by: i64, min: i64| {
+            let changed = if up { value + Fixed::from_int(by) } else { value - Fixed::from_int(by) };
+            changed.max(Fixed::from_int(min))
+        };
+        match self.param {
+            Param::Laps => self.params.total_laps = if up { self.params.total_laps + 1 } else { self.params.total_laps.saturating_sub(1).max(1) },
+            Param::PitPenalty => self.params.pit_stop_penalty = step(self.params.pit_stop_penalty, 5, 0),
+            Param::TyreWear => self.params.tyre_wear_distance = step(self.params.tyre_wear_distance, 10, 10),
+        }
+        self.solve();
+    }
+
+    // Applies a key press; false once the user asked to quit
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                self.position = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.cars.len().saturating_sub(1));
+                self.position = 0;
+            }
+            KeyCode::Right | KeyCode::Char('l') => self.next_lap(),
+            KeyCode::Left | KeyCode::Char('h') => self.previous_lap(),
+            KeyCode::Char('.') => self.tick(),
+            KeyCode::Char(',') => self.position = self.position.saturating_sub(1),
+            KeyCode::Home => self.position = 0,
+            KeyCode::End => self.position = self.segments().len(),
+            KeyCode::Char(' ') => {
+                if self.position == self.segments().len() {
+                    self.position = 0;
+                }
+                self.playing = !self.playing && !self.segments().is_empty();
+            }
+            KeyCode::Tab => {
+                let i = Param::ALL.iter().position(|&p| p == self.param).unwrap_or(0);
+                self.param = Param::ALL[(i + 1) % Param::ALL.len()];
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.adjust(true),
+            KeyCode::Char('-') => self.adjust(false),
+            _ => {}
+        }
+        true
+    }
+}
+
+// Takes over the terminal until the user quits; `step` is the pause between segments while playing
+pub fn run(app: &mut App, step: Duration) -> io::Result<()> {
+    // The track map is drawn by ratatui, so it must come without ANSI colours
+    colored::control::set_override(false);
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, app, step);
+    ratatui::restore();
+    colored::control::unset_override();
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App, step: Duration) -> io::Result<()> {
+    let mut next_tick = Instant::now() + step;
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+        let timeout = if app.playing { next_tick.saturating_duration_since(Instant::now()) } else { Duration::from_secs(3600) };
+        if event::poll(timeout)? {
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && !app.handle_key(key.code)
+            {
+                return Ok(());
+            }
+        } else if app.playing {
+            app.tick();
+            next_tick = Instant::now() + step;
+        }
+    }
+}
+
+// Header with the race parameters, the cars beside the track map, gauges and the current lap
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [header, body, gauges, lap, footer] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(8), Constraint::Length(3), Constraint::Length(9), Constraint::Length(1)]).areas(frame.area());
+    let [cars, track] = Layout::horizontal([Constraint::Length(34), Constraint::Min(20)]).areas(body);
+
+    draw_header(frame, app, header);
+    draw_cars(frame, app, cars);
+    draw_track(frame, app, track);
+    draw_gauges(frame, app, gauges);
+    draw_lap(frame, app, lap);
+    let keys = "q quit  ↑↓ car  ←→ lap  ,. segment  space play  tab parameter  +/- change";
+    frame.render_widget(Line::from(vec![Span::raw(keys).dark_gray(), Span::raw("  "), Span::raw(app.status.as_str()).cyan()]), footer);
+}
+
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let value = |param: Param| match param {
+        Param::Laps => app.params.total_laps.to_string(),
+        Param::PitPenalty => app.params.pit_stop_penalty.to_string(),
+        Param::TyreWear => app.params.tyre_wear_distance.to_string(),
+    };
+    let mut spans = Vec::new();
+    for param in Param::ALL {
+        let text = format!(" {} {} ", param.name(), value(param));
+        spans.push(if param == app.param { Span::raw(text).black().on_yellow() } else { Span::raw(text) });
+        spans.push(Span::raw("│").dark_gray());
+    }
+    spans.push(Span::raw(format!(" {} nodes, {} pit node(s), start {} finish {}", app.race_data.n, app.race_data.pit_nodes.len(), app.params.start_node, app.params.end_node)));
+    let title = format!(" F1 Track │ {} ", app.scenario);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(Block::bordered().title(title.bold())), area);
+}
+
+fn draw_cars(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .cars
+        .iter()
+        .zip(&app.results)
+        .map(|(&i, result)| {
+            let car = &app.race_data.cars[i];
+            let summary = match result {
+                Ok(SolveOutcome { best: Some(p), .. }) => format!("{:>9}  {} pit(s)", p.total_time.to_string(), p.pit_stops.len()),
+                Ok(outcome) => outcome.status.to_string(),
+                Err(e) => e.to_string(),
+            };
+            let style = if matches!(result, Ok(SolveOutcome { best: Some(_), .. })) { Style::default() } else { Style::default().red() };
+            ListItem::new(vec![
+                Line::from(format!("Car {}  {}", i + 1, summary)).style(style),
+                Line::from(format!("  fuel {}, tyre cost {}", car.fuel_capacity, car.tyre_cost)).dark_gray(),
+            ])
+        })
+        .collect();
+    let list = List::new(items).block(Block::bordered().title(" Cars ")).highlight_symbol("▶ ").highlight_style(Modifier::BOLD);
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_track(frame: &mut Frame, app: &App, area: Rect) {
+    let options = MapOptions { width: area.width.saturating_sub(2) as usize, collapse: None };
+    let pages = track_map(&app.race_data, &app.adj, app.params.start_node, app.params.end_node, app.strategy(), &options);
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, page) in pages.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+        lines.extend(page.iter().map(|l| Line::from(l.clone())));
+    }
+    let title = match app.segments().get(app.position.wrapping_sub(1)) {
+        Some(segment) => format!(" Track │ lap {}, at node {} ", segment.lap + 1, segment.to),
+        None => format!(" Track │ on the grid at node {} ", app.params.start_node),
+    };
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
+
+fn draw_gauges(frame: &mut Frame, app: &App, area: Rect) {
+    let [fuel_area, tyre_area, race_area] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(area);
+    let segments = app.segments();
+    let capacity = app.cars.get(app.selected).map_or(Fixed::ZERO, |&i| app.race_data.cars[i].fuel_capacity);
+    // State on arrival at the current node: before the first segment, or after the last one driven
+    let (fuel, tyre) = match app.position.checked_sub(1).and_then(|k| segments.get(k)) {
+        Some(s) => (s.fuel_after, s.tyre_after),
+        None => segments.first().map_or((capacity, Fixed::ZERO), |s| (s.fuel_before, s.tyre_before)),
+    };
+    let ratio = |value: Fixed, of: Fixed| if of.is_positive() { (value.to_f64() / of.to_f64()).clamp(0.0, 1.0) } else { 0.0 };
+
+    let fuel_ratio = ratio(fuel, capacity);
+    let fuel_color = if fuel_ratio < 0.1 { Color::Red } else if fuel_ratio < 0.25 { Color::Yellow } else { Color::Green };
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(" Fuel "))
+        .gauge_style(fuel_color)
+        .ratio(fuel_ratio)
+        .label(format!("{} / {}", fuel, capacity));
+    frame.render_widget(gauge, fuel_area);
+
+    let threshold = app.params.tyre_wear_distance;
+    let worn = tyre > threshold;
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(" Tyre wear "))
+        .gauge_style(if worn { Color::Red } else { Color::Blue })
+        .ratio(ratio(tyre, threshold))
+        .label(format!("{} / {}{}", tyre, threshold, if worn { " worn" } else { "" }));
+    frame.render_widget(gauge, tyre_area);
+
+    let time = app.position.checked_sub(1).and_then(|k| segments.get(k)).map_or(Fixed::ZERO, |s| s.cumulative_time);
+    let total = app.strategy().map_or(Fixed::ZERO, |s| s.total_time);
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(format!(" Lap {}/{} ", app.lap() + 1, app.params.total_laps)))
+        .gauge_style(Color::Magenta)
+        .ratio(ratio(time, total))
+        .label(format!("{} / {}{}", time, total, if app.playing { " ▶" } else { "" }));
+    frame.render_widget(gauge, race_area);
+}
+
+// The current lap's segments, the one just driven highlighted
+fn draw_lap(frame: &mut Frame, app: &App, area: Rect) {
+    let lap = app.lap();
+    let segments = app.segments();
+    let rows: Vec<Row> = segments
+        .iter()
+        .filter(|s| s.lap == lap)
+        .map(|s| {
+            let pit = if s.pit_stop { Cell::from("PIT").red() } else { Cell::from("") };
+            Row::new(vec![
+                Cell::from(format!("{} → {}", s.from, s.to)),
+                pit,
+                Cell::from(s.distance.to_string()),
+                Cell::from(s.time.to_string()),
+                Cell::from(s.cumulative_time.to_string()),
+                Cell::from(format!("{} → {}", s.fuel_before, s.fuel_after)),
+                Cell::from(format!("{} → {}", s.tyre_before, s.tyre_after)),
+                Cell::from(if s.tyre_penalty.is_positive() { format!("+{}", s.tyre_penalty) } else { String::new() }),
+            ])
+        })
+        .collect();
+    let first = segments.iter().position(|s| s.lap == lap).unwrap_or(0);
+    let current = app.position.checked_sub(1).filter(|&k| k >= first && segments.get(k).is_some_and(|s| s.lap == lap)).map(|k| k - first);
+    let widths = [8, 4, 9, 9, 10, 18, 18, 9].map(Constraint::Length);
+    let header = Row::new(["Edge", "Pit", "Distance", "Time", "Race time", "Fuel", "Tyre wear", "Penalty"]).bold();
+    let title = if segments.is_empty() { " No strategy ".to_string() } else { format!(" Lap {} ", lap + 1) };
+    let table = Table::new(rows, widths).header(header).block(Block::bordered().title(title)).row_highlight_style(Style::default().reversed());
+    let mut state = TableState::default().with_selected(current);
+    frame.render_stateful_widget(table, area, &mut state);
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::str::FromStr;
+
+// Number of fractional decimal digits carried by `Fixed`
+pub const DECIMALS: u32 = 3;
+const SCALE: i64 = 10i64.pow(DECIMALS);
+
+// Fixed-point decimal used for distances, fuel, tyre wear and times.
+// Stored as an integer count of thousandths so states stay hashable and totally ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    // Smallest representable step (0.001)
+    pub const EPSILON: Fixed = Fixed(1);
+    pub const MAX: Fixed = Fixed(i64::MAX);
+
+    pub const fn from_int(value: i64) -> Self {
+        Fixed(value * SCALE)
+    }
+
+    pub const fn from_raw(raw: i64) -> Self {
+        Fixed(raw)
+    }
+
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    // Nearest representable value; out-of-range values saturate
+    pub fn from_f64(value: f64) -> Self {
+        Fixed((value * SCALE as f64).round() as i64)
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    // `None` when the sum leaves the representable range
+    pub fn checked_add(self, other: Fixed) -> Option<Fixed> {
+        self.0.checked_add(other.0).map(Fixed)
+    }
+
+    // Product rounded like `*`; `None` when it leaves the representable range
+    pub fn checked_mul(self, other: Fixed) -> Option<Fixed> {
+        let product = self.0 as i128 * other.0 as i128;
+        let half = (SCALE / 2) as i128;
+        let rounded = if product >= 0 { (product + half) / SCALE as i128 } else { (product - half) / SCALE as i128 };
+        i64::try_from(rounded).ok().map(Fixed)
+    }
+
+    pub fn saturating_sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+
+    // Round down to a multiple of `step` (steps of at most EPSILON leave the value untouched)
+    pub fn floor_to(self, step: Fixed) -> Fixed {
+        if step.0 <= 1 {
+            return self;
+        }
+        Fixed(self.0.div_euclid(step.0) * step.0)
+    }
+
+    // Round up to a multiple of `step` (steps of at most EPSILON leave the value untouched)
+    pub fn ceil_to(self, step: Fixed) -> Fixed {
+        if step.0 <= 1 {
+            return self;
+        }
+        let floored = self.0.div_euclid(step.0) * step.0;
+        if floored == self.0 { self } else { Fixed(floored + step.0) }
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, other: Fixed) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, other: Fixed) {
+        self.0 -= other.0;
+    }
+}
+
+// Product of two decimals, rounded half away from zero to the nearest thousandth; panics rather
+// than wrapping when it leaves the representable range
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, other: Fixed) -> Fixed {
+        self.checked_mul(other).expect("fixed-point product out of range")
+    }
+}
+
+impl Sum for Fixed {
+    fn sum<I: Iterator<Item = Fixed>>(iter: I) -> Fixed {
+        iter.fold(Fixed::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl From<u32> for Fixed {
+    fn from(value: u32) -> Self {
+        Fixed::from_int(value as i64)
+    }
+}
+
+impl fmt::Display for Fixed {
+    // Integers print without a fractional part, decimals without trailing zeros
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let whole = abs / SCALE as u64;
+        let frac = abs % SCALE as u64;
+        let text = if frac == 0 {
+            format!("{}{}", sign, whole)
+        } else {
+            let digits = format!("{:0width$}", frac, width = DECIMALS as usize);
+            format!("{}{}.{}", sign, whole, digits.trim_end_matches('0'))
+        };
+        f.pad(&text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFixedError(String);
+
+impl fmt::Display for ParseFixedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseFixedError {}
+
+// Accepts plain decimals such as `40`, `1.35` or `-0.03` with at most `DECIMALS` fractional digits
+impl FromStr for Fixed {
+    type Err = ParseFixedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseFixedError(format!("invalid decimal '{}'", s));
+        let (negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, frac) = match body.split_once('.') {
+            Some((w, f)) => (w, f),
+            None => (body, ""),
+        };
+        if whole.is_empty() && frac.is_empty() {
+            return Err(err());
+        }
+        if !whole.chars().all(|c| c.is_ascii_digit()) || !frac.chars().all(|c| c.is_ascii_digit()) {
+            return Err(err());
+        }
+        if frac.len() > DECIMALS as usize {
+            return Err(ParseFixedError(format!("'{}' has more than {} decimal places", s, DECIMALS)));
+        }
+        let whole_value: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| err())? };
+        let frac_value: i64 = if frac.is_empty() {
+            0
+        } else {
+            frac.parse::<i64>().map_err(|_| err())? * 10i64.pow(DECIMALS - frac.len() as u32)
+        };
+        let raw = whole_value
+            .checked_mul(SCALE)
+            .and_then(|w| w.checked_add(frac_value))
+            .ok_or_else(err)?;
+        Ok(Fixed(if negative { -raw } else { raw }))
+    }
+}
+
+// JSON numbers: integers stay integers, decimals go through their shortest `f64` spelling
+impl serde::Serialize for Fixed {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 % SCALE == 0 {
+            serializer.serialize_i64(self.0 / SCALE)
+        } else {
+            serializer.serialize_f64(self.to_f64())
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Fixed {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FixedVisitor;
+
+        impl serde::de::Visitor<'_> for FixedVisitor {
+            type Value = Fixed;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a number with at most {} decimals", DECIMALS)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Fixed, E> {
+                value.checked_mul(SCALE).map(Fixed).ok_or_else(|| E::custom("number out of range"))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Fixed, E> {
+                i64::try_from(value).map_err(|_| E::custom("number out of range")).and_then(|v| self.visit_i64(v))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Fixed, E> {
+                value.to_string().parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(FixedVisitor)
+    }
+}
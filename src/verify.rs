@@ -0,0 +1,155 @@
+use std::fmt;
+use crate::graph_rcspp::{tyre_penalty, Graph, PathInfo, RaceParams, Segment};
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+
+// A way in which a returned strategy breaks the race rules or disagrees with its own totals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    EmptyPath,
+    WrongStart { expected: usize, found: usize },
+    // Consecutive nodes with no edge between them
+    MissingEdge { index: usize, from: usize, to: usize },
+    // Leaving `from` would need more fuel than the car has left
+    NegativeFuel { index: usize, from: usize, to: usize, fuel: Fixed, distance: Fixed },
+    PitAtNonPitNode { lap: u32, node: usize },
+    // A recorded pit stop that does not match any point of the journey
+    UnusedPitStop { lap: u32, node: usize },
+    WrongFinish { expected_node: usize, expected_lap: u32, node: usize, lap: u32 },
+    TimeMismatch { reported: Fixed, recomputed: Fixed },
+    // The reported timeline disagrees with the replay at this segment (or has the wrong length)
+    SegmentMismatch { index: usize },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::EmptyPath => write!(f, "node sequence is empty"),
+            Discrepancy::WrongStart { expected, found } => write!(f, "path starts at node {} instead of {}", found, expected),
+            Discrepancy::MissingEdge { index, from, to } => write!(f, "step {}: no edge {} -> {}", index, from, to),
+            Discrepancy::NegativeFuel { index, from, to, fuel, distance } => write!(
+                f,
+                "step {}: edge {} -> {} needs {} fuel but only {} is left",
+                index, from, to, distance, fuel
+            ),
+            Discrepancy::PitAtNonPitNode { lap, node } => write!(f, "pit stop on lap {} at node {}, which is not a pit node", lap, node),
+            Discrepancy::UnusedPitStop { lap, node } => write!(f, "pit stop on lap {} at node {} is never reached", lap, node),
+            Discrepancy::WrongFinish { expected_node, expected_lap, node, lap } => write!(
+                f,
+                "path ends at node {} on lap {} instead of node {} on lap {}",
+                node, lap, expected_node, expected_lap
+            ),
+            Discrepancy::TimeMismatch { reported, recomputed } => write!(f, "reported time {} but replay gives {}", reported, recomputed),
+            Discrepancy::SegmentMismatch { index } => write!(f, "timeline segment {} disagrees with the replay", index),
+        }
+    }
+}
+
+// Replays `path_info` through the race rules, independently of the solver's search state.
+// Fuel and tyre wear are rounded with the same resolution the solver used.
+pub fn verify_strategy(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    path_info: &PathInfo,
+) -> Result<(), Vec<Discrepancy>> {
+    let (timeline, mut discrepancies) = replay(race_data, graph, car_config, params, &path_info.node_sequence, &path_info.pit_stops);
+    let recomputed = timeline.last().map(|s| s.cumulative_time).unwrap_or(Fixed::ZERO);
+    if recomputed != path_info.total_time {
+        discrepancies.push(Discrepancy::TimeMismatch { reported: path_info.total_time, recomputed });
+    }
+    if let Some(index) = (0..timeline.len().max(path_info.segments.len())).find(|&i| timeline.get(i) != path_info.segments.get(i)) {
+        discrepancies.push(Discrepancy::SegmentMismatch { index });
+    }
+    if discrepancies.is_empty() { Ok(()) } else { Err(discrepancies) }
+}
+
+// Drives `node_sequence`, pitting where `pit_stops` says, and rebuilds the timeline.
+// Rule violations are collected rather than stopping the replay, except for a missing edge.
+pub fn replay(
+    race_data: &RaceData,
+    graph: &Graph,
+    car_config: &CarConfig,
+    params: &RaceParams,
+    node_sequence: &[usize],
+    pit_stops: &[(u32, usize)],
+) -> (Vec<Segment>, Vec<Discrepancy>) {
+    let mut discrepancies = Vec::new();
+    let mut timeline = Vec::new();
+    let Some(&first) = node_sequence.first() else {
+        return (timeline, vec![Discrepancy::EmptyPath]);
+    };
+    if first != params.start_node {
+        discrepancies.push(Discrepancy::WrongStart { expected: params.start_node, found: first });
+    }
+    for &(lap, node) in pit_stops {
+        if !race_data.pit_nodes.contains(&node) {
+            discrepancies.push(Discrepancy::PitAtNonPitNode { lap, node });
+        }
+    }
+
+    let resolution = params.resolution;
+    let mut fuel = car_config.fuel_capacity.floor_to(resolution.fuel);
+    let mut tyre = Fixed::ZERO;
+    let mut lap = 0;
+    let mut time = Fixed::ZERO;
+    let mut pending_pits = pit_stops.iter().peekable();
+
+    for (index, w) in node_sequence.windows(2).enumerate() {
+        let (from, to) = (w[0], w[1]);
+        // Parallel edges: the solver can only gain from the shortest one
+        let Some(distance) = graph.adj.get(&from).and_then(|n| n.iter().filter(|&&(v, _)| v == to).map(|&(_, d)| d).min()) else {
+            discrepancies.push(Discrepancy::MissingEdge { index, from, to });
+            return (timeline, discrepancies);
+        };
+
+        let (fuel_before, tyre_before, time_before) = (fuel, tyre, time);
+        let pit_stop = pending_pits.peek().is_some_and(|&&(pit_lap, pit_node)| pit_lap == lap && pit_node == from);
+        if pit_stop {
+            pending_pits.next();
+            fuel = car_config.fuel_capacity;
+            tyre = Fixed::ZERO;
+            time += params.pit_stop_penalty;
+        }
+
+        if fuel < distance {
+            discrepancies.push(Discrepancy::NegativeFuel { index, from, to, fuel, distance });
+        }
+        fuel = (fuel - distance).floor_to(resolution.fuel);
+        tyre = (tyre + distance).ceil_to(resolution.tyre);
+        let tyre_penalty = tyre_penalty(tyre, params.tyre_wear_distance, car_config.tyre_cost);
+        time += distance + tyre_penalty;
+        timeline.push(Segment {
+            lap,
+            from,
+            to,
+            pit_stop,
+            distance,
+            time: time - time_before,
+            cumulative_time: time,
+            fuel_before,
+            fuel_after: fuel,
+            tyre_before,
+            tyre_after: tyre,
+            tyre_penalty,
+        });
+        if to == params.start_node && from != params.start_node {
+            lap += 1;
+        }
+    }
+
+    for &(lap, node) in pending_pits {
+        discrepancies.push(Discrepancy::UnusedPitStop { lap, node });
+    }
+    let last = *node_sequence.last().unwrap_or(&first);
+    if last != params.end_node || lap + 1 != params.total_laps {
+        discrepancies.push(Discrepancy::WrongFinish {
+            expected_node: params.end_node,
+            expected_lap: params.total_laps.saturating_sub(1),
+            node: last,
+            lap,
+        });
+    }
+    (timeline, discrepancies)
+}
@@ -1,10 +1,16 @@
 use colored::*;
-use crate::input_parser::RaceData;
-use petgraph::graph::Graph as PetGraph;
-use petgraph::dot::{Dot, Config};
-use std::process::Command;
+use crate::input_parser::{CarConfig, RaceData};
+use crate::units::Fixed;
+use crate::graph_rcspp::{PathInfo, Segment};
+use crate::render::{self, attr};
+use crate::charts::{self, Metric, Trace, GLYPHS};
+use crate::comparison::{describe, pit_list, signed, Comparison};
+use crate::track_map::{track_map, MapOptions};
+use graphviz_rust::dot_structures::{Graph, Stmt};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::f64::consts::PI;
 
-pub fn print_graph_visualization(race_data: &RaceData) {
+pub fn print_graph_visualization(race_data: &RaceData, start: usize, end: usize) {
     println!("\n{}", "=== TRACK VISUALIZATION ===".bold().cyan());
     
     // Print basic track info
@@ -15,14 +21,11 @@ pub fn print_graph_visualization(race_data: &RaceData) {
     );
     
     // Create adjacency list for visualization
-    let mut adj: Vec<Vec<(usize, u32)>> = vec![Vec::new(); race_data.n];
-    for edge in &race_data.edges {
-        adj[edge.u].push((edge.v, edge.distance));
-    }
+    let adj = race_data.adjacency();
     
     // Print nodes with their connections
     println!("\n{}", "Track Layout:".bold().yellow());
-    for i in 0..race_data.n {
+    for (i, neighbors) in adj.iter().enumerate() {
         let node_color = if race_data.pit_nodes.contains(&i) {
             i.to_string().red().bold()
         } else {
@@ -31,11 +34,11 @@ pub fn print_graph_visualization(race_data: &RaceData) {
         
         print!("Node {}: ", node_color);
         
-        if adj[i].is_empty() {
+        if neighbors.is_empty() {
             println!("{}", "DEAD END".red());
         } else {
             let mut connections = Vec::new();
-            for &(neighbor, dist) in &adj[i] {
+            for &(neighbor, dist) in neighbors {
                 let neighbor_color = if race_data.pit_nodes.contains(&neighbor) {
                     neighbor.to_string().red().bold()
                 } else {
@@ -55,46 +58,24 @@ pub fn print_graph_visualization(race_data: &RaceData) {
         }
     }
     
-    // Print a simple ASCII track representation
-    println!("\n{}", "ASCII Track Map:".bold().yellow());
-    print_ascii_track(race_data, &adj);
+    // Draw the track as a box-drawing map
+    println!("\n{}", "Track Map:".bold().yellow());
+    print_ascii_track(race_data, &adj, start, end);
     
     // Print car configurations
     println!("\n{}", "Car Configurations:".bold().magenta());
     for (i, car) in race_data.cars.iter().enumerate() {
-        let car_color = match i % 3 {
-            0 => Color::Green,
-            1 => Color::Blue,
-            _ => Color::Magenta,
-        };
+        let color = car_color(i);
         println!("  Car {}: Fuel={}, Tyre Cost={}", 
-            (i + 1).to_string().color(car_color),
-            car.fuel_capacity.to_string().color(car_color),
-            car.tyre_cost.to_string().color(car_color)
+            (i + 1).to_string().color(color),
+            car.fuel_capacity.to_string().color(color),
+            car.tyre_cost.to_string().color(color)
         );
     }
 }
 
-fn print_ascii_track(race_data: &RaceData, _adj: &Vec<Vec<(usize, u32)>>) {
-    // Simple linear representation for small tracks
-    if race_data.n <= 10 {
-        let mut track_line = String::new();
-        for i in 0..race_data.n {
-            if race_data.pit_nodes.contains(&i) {
-                track_line.push_str(&format!("[P{}]", i));
-            } else {
-                track_line.push_str(&format!("[{}]", i));
-            }
-            if i < race_data.n - 1 {
-                track_line.push_str(" -> ");
-            }
-        }
-        println!("  {}", track_line);
-    } else {
-        // For larger tracks, show a more compact representation
-        println!("  Track too large for ASCII visualization ({} nodes)", race_data.n);
-        println!("  Use the detailed node connections above for reference");
-    }
+fn print_ascii_track(race_data: &RaceData, adj: &[Vec<(usize, Fixed)>], start: usize, end: usize) {
+    print_track_map(race_data, adj, start, end, None);
     
     // Show edge distances
     println!("\n  Edge Distances:");
@@ -117,148 +98,657 @@ fn print_ascii_track(race_data: &RaceData, _adj: &Vec<Vec<(usize, u32)>>) {
     }
 }
 
+// The track map with the strategy's edges drawn heavy in their laps' colours
+pub fn print_strategy_map(race_data: &RaceData, start: usize, end: usize, path_info: &PathInfo) {
+    let adj = race_data.adjacency();
+    println!("\n{}", "Strategy Map:".bold().yellow());
+    print_track_map(race_data, &adj, start, end, Some(path_info));
+}
+
+fn print_track_map(race_data: &RaceData, adj: &[Vec<(usize, Fixed)>], start: usize, end: usize, strategy: Option<&PathInfo>) {
+    let options = MapOptions::default();
+    let pages = track_map(race_data, adj, start, end, strategy, &options);
+    for (i, page) in pages.iter().enumerate() {
+        if pages.len() > 1 {
+            println!("  {}", format!("({}/{})", i + 1, pages.len()).dimmed());
+        }
+        for line in page {
+            println!("  {}", line);
+        }
+    }
+    let mut legend = vec!["S start", "F finish", "[Pn] pit", "▼▲ arc ends"];
+    if pages.iter().flatten().any(|l| l.contains('┄') || l.contains('┅')) {
+        legend.push("┄ folded nodes");
+    }
+    if let Some(path_info) = strategy {
+        legend.push("━ driven");
+        if !path_info.pit_stops.is_empty() {
+            legend.push("* pit stop");
+        }
+    }
+    println!("  {}", legend.join("  ").dimmed());
+}
+
 pub fn print_race_strategy(car_id: usize, path_info: &crate::graph_rcspp::PathInfo, race_data: &RaceData) {
-    let car_color = match car_id % 3 {
-        0 => Color::Green,
-        1 => Color::Blue,
-        _ => Color::Magenta,
-    };
-    
-    println!("\n{}", format!("=== CAR {} RACE STRATEGY ===", car_id + 1).bold().color(car_color));
-    println!("Total Race Time: {} units", path_info.total_time.to_string().color(car_color));
+    print!("{}", format_race_strategy(car_id, path_info, race_data));
+}
+
+// The strategy summary printed by `print_race_strategy`, as text
+pub fn format_race_strategy(car_id: usize, path_info: &crate::graph_rcspp::PathInfo, race_data: &RaceData) -> String {
+    use std::fmt::Write;
+
+    let color = car_color(car_id);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "\n{}", format!("=== CAR {} RACE STRATEGY ===", car_id + 1).bold().color(color));
+    let _ = writeln!(out, "Total Race Time: {} units", path_info.total_time.to_string().color(color));
     
     if path_info.pit_stops.is_empty() {
-        println!("Pit Stops: {}", "None".yellow());
+        let _ = writeln!(out, "Pit Stops: {}", "None".yellow());
     } else {
-        println!("Pit Stops:");
+        let _ = writeln!(out, "Pit Stops:");
         for (lap, node) in &path_info.pit_stops {
-            println!("  Lap {} at Node {} (PIT)", 
+            let _ = writeln!(out, "  Lap {} at Node {} (PIT)", 
                 lap.to_string().color(Color::Yellow),
                 node.to_string().red().bold()
             );
         }
     }
     
-    println!("Race Path: {}", 
+    let _ = writeln!(out, "Race Path: {}", 
         path_info.node_sequence.iter()
             .map(|&node| {
                 if race_data.pit_nodes.contains(&node) {
                     format!("[P{}]", node).red().bold().to_string()
                 } else {
-                    format!("[{}]", node).color(car_color).to_string()
+                    format!("[{}]", node).color(color).to_string()
                 }
             })
             .collect::<Vec<_>>()
             .join(" -> ")
     );
+    out
+}
+
+pub fn print_comparison(comparison: &Comparison) {
+    print!("{}", format_comparison(comparison));
+}
+
+// Segments listed per car under "where the gap comes from"
+const GAP_SEGMENTS: usize = 8;
+
+// Strategies side by side against the fastest: each lap's time, delta and pit stops, the gap at
+// every lap end as bars, and the segments that account for each car's gap
+pub fn format_comparison(comparison: &Comparison) -> String {
+    use std::fmt::Write;
+
+    let reference = comparison.reference;
+    let mut out = String::new();
+    let _ = writeln!(out, "\n{}", "=== STRATEGY COMPARISON ===".bold().cyan());
+    let _ = writeln!(out, "Against car {}, the fastest at {} units",
+        (reference + 1).to_string().color(car_color(reference)), comparison.reference_time);
+
+    // Lap by lap: the reference's time and pits, then each car's with its delta and running gap
+    let mut header = format!("\n{:<6} {:<22}", "Lap", format!("Car {}", reference + 1));
+    for car in &comparison.cars {
+        let _ = write!(header, " {:<40}", format!("Car {}", car.car + 1));
+    }
+    let _ = writeln!(out, "{}", header.trim_end());
+    let laps = comparison.cars.first().map_or(0, |c| c.laps.len());
+    for lap in 0..laps {
+        let first = &comparison.cars[0].laps[lap];
+        let mut line = format!("{:<6} {:<22}", lap + 1, format!("{} {}", first.reference_time, lap_pits(&first.reference_pits)));
+        for car in &comparison.cars {
+            let l = &car.laps[lap];
+            let cell = format!("{} {} gap {} {}", l.time, signed(l.delta), signed(l.gap), lap_pits(&l.pits));
+            let padding = " ".repeat(40usize.saturating_sub(cell.chars().count()));
+            let cell = if l.pits_differ() { cell.yellow().to_string() } else { cell };
+            let _ = write!(line, " {}{}", cell, padding);
+        }
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+    if comparison.cars.iter().any(|c| c.laps.iter().any(|l| l.pits_differ())) {
+        let _ = writeln!(out, "{}", "Laps in yellow pit differently from the fastest car".dimmed());
+    }
+
+    // The gap at every lap end, as bars scaled to the largest
+    let widest = comparison.cars.iter().flat_map(|c| &c.laps).map(|l| l.gap.raw().abs()).max().unwrap_or(0);
+    let _ = writeln!(out, "\nGap to car {} at each lap end:", reference + 1);
+    for car in &comparison.cars {
+        for l in &car.laps {
+            let length = if widest > 0 { ((l.gap.raw().abs() as f64 / widest as f64) * 30.0).round() as usize } else { 0 };
+            let bar = if l.gap >= Fixed::ZERO { "█".repeat(length).red() } else { "░".repeat(length).green() };
+            let line = format!("  {} lap {:<3} {:>10}  {}", format!("Car {}", car.car + 1).color(car_color(car.car)), l.lap + 1, signed(l.gap), bar);
+            let _ = writeln!(out, "{}", line.trim_end());
+        }
+    }
+
+    // The segments behind each car's gap, largest first
+    for car in &comparison.cars {
+        let name = (car.car + 1).to_string().color(car_color(car.car));
+        if car.gap == Fixed::ZERO {
+            let _ = writeln!(out, "\nWhere car {} ties with car {}:", name, reference + 1);
+        } else {
+            let _ = writeln!(out, "\nWhere car {} loses {} units to car {}:", name, car.gap, reference + 1);
+        }
+        if car.contributions.is_empty() {
+            let _ = writeln!(out, "  Same times on every segment");
+        }
+        for c in car.contributions.iter().take(GAP_SEGMENTS) {
+            let edge = format!("{} -> {}", c.from, c.to);
+            let delta = if c.delta >= Fixed::ZERO { signed(c.delta).red() } else { signed(c.delta).green() };
+            let _ = writeln!(out, "  lap {:<3} {:<10} {:>10}  {}", c.lap + 1, edge, delta, describe(c, car.car, reference));
+        }
+        if car.contributions.len() > GAP_SEGMENTS {
+            let rest: Fixed = car.contributions[GAP_SEGMENTS..].iter().map(|c| c.delta).sum();
+            let _ = writeln!(out, "  {} more segment(s) {}", car.contributions.len() - GAP_SEGMENTS, signed(rest));
+        }
+    }
+    out
+}
+
+// A lap's pit stops for the comparison table, blank when there are none
+fn lap_pits(pits: &[usize]) -> String {
+    if pits.is_empty() { String::new() } else { pit_list(pits) }
+}
+
+// The whole track as a Graphviz digraph, with each edge labelled by its distance
+pub fn track_dot(race_data: &RaceData) -> String {
+    render::to_dot(&track_graph(race_data))
 }
 
-pub fn export_dot(race_data: &RaceData, output_path: &str) -> std::io::Result<()> {
-    let mut g = PetGraph::<String, String>::new();
-    let mut nodes = Vec::with_capacity(race_data.n);
+pub fn track_graph(race_data: &RaceData) -> Graph {
+    let mut stmts = graph_header(race_data);
+    for e in &race_data.edges {
+        stmts.push(render::edge(e.u, e.v, vec![attr("label", e.distance)]));
+    }
+    render::digraph(stmts)
+}
+
+// Opening of every track graph: layout, node styles and the nodes, with pit nodes boxed in red
+fn graph_header(race_data: &RaceData) -> Vec<Stmt> {
+    let mut stmts = vec![
+        Stmt::Attribute(attr("rankdir", "LR")),
+        render::node_defaults(vec![attr("shape", "circle"), attr("style", "filled"), attr("fillcolor", "white")]),
+    ];
     for i in 0..race_data.n {
-        let label = if race_data.pit_nodes.contains(&i) { format!("P{}", i) } else { i.to_string() };
-        nodes.push(g.add_node(label));
+        if race_data.pit_nodes.contains(&i) {
+            stmts.push(render::node(i, vec![attr("shape", "box"), attr("fillcolor", "mistyrose"), attr("color", "red"), attr("label", format!("P{}", i))]));
+        } else {
+            stmts.push(render::node(i, vec![attr("label", i)]));
+        }
     }
+    stmts
+}
+
+// Per-lap highlight colours in DOT exports, cycled when a race has more laps
+pub const LAP_COLORS: [&str; 5] = ["blue", "green", "red", "orange", "purple"];
+
+// Highlighted graphs of a strategy: one per lap (numbered from 1), then the whole race with every lap's colour (`None`)
+pub fn lap_graphs(race_data: &RaceData, path_info: &PathInfo) -> Vec<(Option<u32>, Graph)> {
+    let mut graphs = Vec::new();
+    let laps = path_info.segments.last().map_or(0, |s| s.lap + 1);
+    for lap in 0..laps {
+        let segments: Vec<Segment> = path_info.segments.iter().filter(|s| s.lap == lap).cloned().collect();
+        if !segments.is_empty() {
+            graphs.push((Some(lap + 1), highlighted_graph(race_data, &segments, &LAP_COLORS)));
+        }
+    }
+    graphs.push((None, highlighted_graph(race_data, &path_info.segments, &LAP_COLORS)));
+    graphs
+}
+
+pub fn highlighted_dot(race_data: &RaceData, segments: &[Segment], lap_colors: &[&str]) -> String {
+    render::to_dot(&highlighted_graph(race_data, segments, lap_colors))
+}
+
+// The track with the driven `segments` drawn over it in their lap's colour, each labelled with
+// what driving it cost. Edges the strategy does not use stay grey with just their distance.
+pub fn highlighted_graph(race_data: &RaceData, segments: &[Segment], lap_colors: &[&str]) -> Graph {
+    let mut stmts = graph_header(race_data);
     for e in &race_data.edges {
-        g.add_edge(nodes[e.u], nodes[e.v], format!("{}", e.distance));
+        if !segments.iter().any(|s| (s.from, s.to, s.distance) == (e.u, e.v, e.distance)) {
+            stmts.push(render::edge(e.u, e.v, vec![attr("label", e.distance), attr("color", "gray80")]));
+        }
+    }
+    for segment in segments {
+        let color = lap_colors.get(segment.lap as usize % lap_colors.len().max(1)).unwrap_or(&"blue");
+        stmts.push(render::edge(segment.from, segment.to, vec![
+            attr("label", segment_label(segment)),
+            attr("color", color),
+            attr("fontcolor", color),
+            attr("penwidth", "3.0"),
+        ]));
+    }
+    render::digraph(stmts)
+}
+
+// Distance, then the segment's time, fuel burned, tyre wear and tyre penalty, one per line
+fn segment_label(segment: &Segment) -> String {
+    // A pit stop fits fresh tyres, so only the wear from this edge counts
+    let worn_before = if segment.pit_stop { Fixed::ZERO } else { segment.tyre_before };
+    let mut label = format!("{}", segment.distance);
+    if segment.pit_stop {
+        label.push_str("\\nPIT");
+    }
+    label.push_str(&format!("\\nt +{} = {}", segment.time, segment.cumulative_time));
+    label.push_str(&format!("\\nfuel -{} ({} left)", segment.distance, segment.fuel_after));
+    label.push_str(&format!("\\ntyre +{} ({} worn)", segment.tyre_after - worn_before, segment.tyre_after));
+    if segment.tyre_penalty.is_positive() {
+        label.push_str(&format!("\\ntyre penalty +{}", segment.tyre_penalty));
     }
-    let dot = format!("{:?}", Dot::with_config(&g, &[Config::EdgeNoLabel]));
-    std::fs::write(output_path, dot)
+    label
 }
 
-pub fn export_highlighted_dots(
-    race_data: &RaceData,
-    path_info: &crate::graph_rcspp::PathInfo,
-    start_node: usize,
-    total_laps: u32,
-    output_prefix: &str,
-) -> std::io::Result<()> {
-    // Build adjacency for quick edge distance lookup
-    let mut adj: Vec<std::collections::HashMap<usize, u32>> = vec![Default::default(); race_data.n];
+pub fn print_diagnosis(diagnosis: &crate::diagnosis::Diagnosis) {
+    println!("{}", "Why no strategy exists:".bold().red());
+    if diagnosis.causes.is_empty() {
+        println!("  {}", "No single cause found; the constraints only conflict in combination".yellow());
+    }
+    for cause in &diagnosis.causes {
+        println!("  - {}", cause);
+    }
+}
+
+// The track with the nodes and edges named by `diagnosis` in red
+pub fn diagnosis_graph(race_data: &RaceData, diagnosis: &crate::diagnosis::Diagnosis) -> Graph {
+    let bad_nodes = diagnosis.nodes();
+    let bad_edges = diagnosis.edges();
+    let mut stmts = vec![
+        Stmt::Attribute(attr("rankdir", "LR")),
+        render::node_defaults(vec![attr("shape", "circle"), attr("style", "filled"), attr("fillcolor", "white")]),
+    ];
+    for i in 0..race_data.n {
+        let shape = if race_data.pit_nodes.contains(&i) { "box" } else { "circle" };
+        let label = if race_data.pit_nodes.contains(&i) { format!("P{}", i) } else { i.to_string() };
+        if bad_nodes.contains(&i) {
+            stmts.push(render::node(i, vec![attr("shape", shape), attr("fillcolor", "red"), attr("fontcolor", "white"), attr("label", label)]));
+        } else {
+            stmts.push(render::node(i, vec![attr("shape", shape), attr("label", label)]));
+        }
+    }
     for e in &race_data.edges {
-        adj[e.u].insert(e.v, e.distance);
-    }
-
-    // Function to write one DOT with highlighted edges and lap colors
-    let write_dot = |segments: &[(usize, usize)], lap_colors: &[&str], file_path: &str| -> std::io::Result<()> {
-        let mut dot = String::new();
-        dot.push_str("digraph Track {\n");
-        dot.push_str("  rankdir=LR;\n");
-        dot.push_str("  node [shape=circle, style=filled, fillcolor=white];\n");
-        // Nodes
-        for i in 0..race_data.n {
-            if race_data.pit_nodes.contains(&i) {
-                dot.push_str(&format!("  {} [shape=box, fillcolor=mistyrose, color=red, label=\"P{}\"];\n", i, i));
-            } else {
-                dot.push_str(&format!("  {} [label=\"{}\"];\n", i, i));
+        if bad_edges.contains(&(e.u, e.v)) {
+            stmts.push(render::edge(e.u, e.v, vec![
+                attr("label", e.distance),
+                attr("color", "red"),
+                attr("fontcolor", "red"),
+                attr("penwidth", "3.0"),
+                attr("style", "dashed"),
+            ]));
+        } else {
+            stmts.push(render::edge(e.u, e.v, vec![attr("label", e.distance), attr("color", "gray80")]));
+        }
+    }
+    render::digraph(stmts)
+}
+
+// Edges of pit lanes: into and out of a pit node the main line bypasses with a direct edge.
+// Pit nodes the racing line runs through are drawn as pit boxes on the track instead.
+pub fn pit_lane_edges(race_data: &RaceData) -> BTreeSet<(usize, usize)> {
+    let direct: HashSet<(usize, usize)> = race_data.edges.iter().map(|e| (e.u, e.v)).collect();
+    let mut lane = BTreeSet::new();
+    for &pit in &race_data.pit_nodes {
+        for into in race_data.edges.iter().filter(|e| e.v == pit && e.u != pit) {
+            for out in race_data.edges.iter().filter(|e| e.u == pit && e.v != pit) {
+                if direct.contains(&(into.u, out.v)) {
+                    lane.insert((into.u, pit));
+                    lane.insert((pit, out.v));
+                }
             }
         }
-        // Edges default
-        for e in &race_data.edges {
-            let lbl = adj[e.u].get(&e.v).cloned().unwrap_or(0);
-            dot.push_str(&format!("  {} -> {} [label=\"{}\", color=gray80];\n", e.u, e.v, lbl));
+    }
+    lane
+}
+
+// Node positions for the circuit map: the input's, or evenly spaced round a circle when it has none
+pub fn circuit_positions(race_data: &RaceData) -> Vec<(f64, f64)> {
+    match &race_data.positions {
+        Some(positions) => positions.iter().map(|p| (p.x.to_f64(), p.y.to_f64())).collect(),
+        None => (0..race_data.n)
+            .map(|i| {
+                let angle = PI / 2.0 - 2.0 * PI * i as f64 / race_data.n.max(1) as f64;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect(),
+    }
+}
+
+const MAP_WIDTH: f64 = 900.0;
+const MAP_MARGIN: f64 = 60.0;
+const TRACK_WIDTH: f64 = 14.0;
+
+// The circuit drawn to scale from the node positions: track and pit lane, direction of travel,
+// pit boxes, the start/finish line at `start` (and a separate finish at `end`) and a scale bar.
+// With a strategy, each lap's segments are drawn over the track in their lap colour, side by side
+// where laps share a segment, with every pit stop marked and lap times in the legend.
+pub fn circuit_svg(race_data: &RaceData, start: usize, end: usize, strategy: Option<&PathInfo>) -> String {
+    use std::fmt::Write;
+
+    let points = circuit_positions(race_data);
+    let (min_x, max_x) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    let (min_y, max_y) = points.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    let (span_x, span_y) = ((max_x - min_x).max(0.0), (max_y - min_y).max(0.0));
+    let inner = MAP_WIDTH - 2.0 * MAP_MARGIN;
+    // One scale for both axes keeps the circuit's shape
+    let scale = if span_x.max(span_y) > 0.0 { inner / span_x.max(span_y) } else { 1.0 };
+    let height = span_y * scale + 2.0 * MAP_MARGIN;
+    let laps: Vec<u32> = strategy.map_or(Vec::new(), |s| {
+        let mut laps: Vec<u32> = s.segments.iter().map(|seg| seg.lap).collect();
+        laps.dedup();
+        laps
+    });
+    let legend_lines = 2 + laps.len() + usize::from(strategy.is_some());
+    let total_height = height + 18.0 * legend_lines as f64 + 10.0;
+    // Input y points up, SVG y points down
+    let at = |node: usize| {
+        let (x, y) = points.get(node).copied().unwrap_or((min_x, min_y));
+        (MAP_MARGIN + (x - min_x) * scale + (inner - span_x * scale) / 2.0, MAP_MARGIN + (max_y - y) * scale)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}" font-family="Helvetica, Arial, sans-serif" font-size="12">"#,
+        MAP_WIDTH, total_height, MAP_WIDTH, total_height
+    );
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#eef3e8"/>"##);
+
+    // Track surface first, then the pit lane, so the main line reads as the circuit
+    let pit_lane = pit_lane_edges(race_data);
+    let line = |svg: &mut String, a: (f64, f64), b: (f64, f64), style: &str| {
+        let _ = writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" {}/>"#, a.0, a.1, b.0, b.1, style);
+    };
+    for e in race_data.edges.iter().filter(|e| e.u != e.v && !pit_lane.contains(&(e.u, e.v))) {
+        line(&mut svg, at(e.u), at(e.v), &format!(r##"stroke="#555" stroke-width="{}" stroke-linecap="round""##, TRACK_WIDTH));
+    }
+    for e in race_data.edges.iter().filter(|e| e.u != e.v && !pit_lane.contains(&(e.u, e.v))) {
+        line(&mut svg, at(e.u), at(e.v), r#"stroke="white" stroke-width="1" stroke-dasharray="8,8""#);
+    }
+    for &(u, v) in &pit_lane {
+        line(&mut svg, at(u), at(v), &format!(r##"stroke="#999" stroke-width="{}" stroke-linecap="round""##, TRACK_WIDTH / 2.0));
+        line(&mut svg, at(u), at(v), r#"stroke="red" stroke-width="1.5" stroke-dasharray="4,4""#);
+    }
+    // Chevrons half way along each edge show the direction of travel
+    for e in race_data.edges.iter().filter(|e| e.u != e.v) {
+        let (a, b) = (at(e.u), at(e.v));
+        let Some((dx, dy)) = direction(a, b) else { continue };
+        let mid = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        let tip = (mid.0 + dx * 4.0, mid.1 + dy * 4.0);
+        let back = (mid.0 - dx * 4.0, mid.1 - dy * 4.0);
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="none" stroke="white" stroke-width="1.5"/>"#,
+            back.0 - dy * 4.0, back.1 + dx * 4.0, tip.0, tip.1, back.0 + dy * 4.0, back.1 - dx * 4.0
+        );
+    }
+
+    // Start and finish lines, across the track at their node
+    let crossing = |node: usize| {
+        race_data
+            .edges
+            .iter()
+            .find(|e| e.u == node && e.u != e.v)
+            .and_then(|e| direction(at(e.u), at(e.v)))
+            .or_else(|| race_data.edges.iter().find(|e| e.v == node && e.u != e.v).and_then(|e| direction(at(e.u), at(e.v))))
+            .unwrap_or((1.0, 0.0))
+    };
+    let flags: Vec<(usize, &str)> = if start == end { vec![(start, "START/FINISH")] } else { vec![(start, "START"), (end, "FINISH")] };
+    for (node, name) in flags {
+        if node >= race_data.n {
+            continue;
         }
-        // Highlighted segments with lap-specific colors and annotations
-        for (i, (u, v)) in segments.iter().enumerate() {
-            let lbl = adj[*u].get(v).cloned().unwrap_or(0);
-            let color = lap_colors.get(i % lap_colors.len()).unwrap_or(&"blue");
-            // Add timing and resource info to edge labels
-            let edge_label = format!("{} (t:{}, f:-{}, ty:+{})", lbl, lbl, lbl, lbl);
-            dot.push_str(&format!("  {} -> {} [label=\"{}\", color={}, penwidth=3.0];\n", u, v, edge_label, color));
+        let (x, y) = at(node);
+        let (dx, dy) = crossing(node);
+        let angle = dy.atan2(dx).to_degrees();
+        let _ = write!(svg, r#"<g transform="translate({:.1},{:.1}) rotate({:.1})">"#, x, y, angle);
+        // Two rows of chequers spanning the track
+        let square = TRACK_WIDTH / 4.0;
+        for row in 0..2 {
+            for col in 0..6 {
+                let fill = if (row + col) % 2 == 0 { "black" } else { "white" };
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"/>"#,
+                    (row as f64 - 1.0) * square, (col as f64 - 3.0) * square, square, square, fill
+                );
+            }
         }
-        dot.push_str("}\n");
-        std::fs::write(file_path, dot)
-    };
+        let _ = writeln!(svg, "</g>");
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-weight="bold" text-anchor="middle">{}</text>"#, x - dy * 26.0, y + dx * 26.0 + 4.0, name);
+    }
 
-    // Split the path by laps using start_node boundaries
-    let mut laps: Vec<Vec<usize>> = Vec::new();
-    let mut current: Vec<usize> = Vec::new();
-    for (idx, &node) in path_info.node_sequence.iter().enumerate() {
-        if idx == 0 { current.push(node); continue; }
-        current.push(node);
-        if node == start_node {
-            laps.push(current.clone());
-            current.clear();
-            current.push(node); // start next lap from start node
-            if laps.len() as u32 >= total_laps { break; }
+    if let Some(strategy) = strategy {
+        // Laps sharing a segment sit side by side across the track
+        let offset = |lap: u32| {
+            let slot = laps.iter().position(|&l| l == lap).unwrap_or(0) as f64;
+            (slot - (laps.len() as f64 - 1.0) / 2.0) * (TRACK_WIDTH / laps.len().max(1) as f64).min(4.0)
+        };
+        for segment in strategy.segments.iter().filter(|s| s.from != s.to) {
+            let (a, b) = (at(segment.from), at(segment.to));
+            let Some((dx, dy)) = direction(a, b) else { continue };
+            let o = offset(segment.lap);
+            let color = LAP_COLORS[segment.lap as usize % LAP_COLORS.len()];
+            line(
+                &mut svg,
+                (a.0 - dy * o, a.1 + dx * o),
+                (b.0 - dy * o, b.1 + dx * o),
+                &format!(r#"stroke="{}" stroke-width="3" stroke-linecap="round" opacity="0.85""#, color),
+            );
+        }
+        // Repeat stops at the same node stack their labels
+        let mut stops_at: HashMap<usize, usize> = HashMap::new();
+        for segment in strategy.segments.iter().filter(|s| s.pit_stop) {
+            let (x, y) = at(segment.from);
+            let color = LAP_COLORS[segment.lap as usize % LAP_COLORS.len()];
+            let earlier = stops_at.entry(segment.from).or_insert(0);
+            let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="none" stroke="{}" stroke-width="3"/>"#, x, y, 11 + 4 * *earlier, color);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" fill="{}" font-weight="bold">PIT lap {}</text>"#,
+                x + 14.0, y - 10.0 - 14.0 * *earlier as f64, color, segment.lap + 1
+            );
+            *earlier += 1;
         }
     }
-    if !current.is_empty() && (laps.len() as u32) < total_laps { laps.push(current); }
 
-    // Ensure images/ directory exists
-    std::fs::create_dir_all("images")?;
+    // Nodes on top: pit nodes as red boxes, the rest as small markers
+    for i in 0..race_data.n {
+        let (x, y) = at(i);
+        if race_data.pit_nodes.contains(&i) {
+            let _ = writeln!(svg, r#"<rect x="{:.1}" y="{:.1}" width="20" height="14" rx="3" fill="mistyrose" stroke="red"/>"#, x - 10.0, y - 7.0);
+            let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-size="9" fill="red" text-anchor="middle">P{}</text>"#, x, y + 3.0, i);
+        } else {
+            let _ = writeln!(svg, r##"<circle cx="{:.1}" cy="{:.1}" r="4" fill="white" stroke="#222"/>"##, x, y);
+            let _ = writeln!(svg, r##"<text x="{:.1}" y="{:.1}" font-size="10" fill="#222">{}</text>"##, x + 6.0, y - 6.0, i);
+        }
+    }
 
-    // Lap colors: blue, green, red, orange, purple
-    let lap_colors = ["blue", "green", "red", "orange", "purple"];
+    // Legend below the map: scale, layout source, then the strategy's laps
+    let mut y = height + 14.0;
+    if race_data.positions.is_some() && scale > 0.0 {
+        let length = round_length(inner / 4.0 / scale);
+        let _ = writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black" stroke-width="3"/>"#, MAP_MARGIN, y, MAP_MARGIN + length * scale, y);
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}">{}</text>"#, MAP_MARGIN + length * scale + 8.0, y + 4.0, Fixed::from_f64(length));
+    } else {
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}">No positions in the input: nodes spaced evenly, not to scale</text>"#, MAP_MARGIN, y + 4.0);
+    }
+    y += 18.0;
+    let _ = writeln!(
+        svg,
+        r##"<text x="{:.1}" y="{:.1}"><tspan fill="#555">━ track</tspan>  <tspan fill="red">┅ pit lane</tspan>  <tspan fill="red">▭ pit box</tspan></text>"##,
+        MAP_MARGIN, y + 4.0
+    );
+    if let Some(strategy) = strategy {
+        y += 18.0;
+        let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}" font-weight="bold">Total time {}, {} pit stop(s)</text>"#, MAP_MARGIN, y + 4.0, strategy.total_time, strategy.pit_stops.len());
+        for &lap in &laps {
+            y += 18.0;
+            let color = LAP_COLORS[lap as usize % LAP_COLORS.len()];
+            let time: Fixed = strategy.segments.iter().filter(|s| s.lap == lap).map(|s| s.time).sum();
+            let _ = writeln!(svg, r#"<rect x="{:.1}" y="{:.1}" width="24" height="4" fill="{}"/>"#, MAP_MARGIN, y, color);
+            let _ = writeln!(svg, r#"<text x="{:.1}" y="{:.1}">Lap {}: {}</text>"#, MAP_MARGIN + 32.0, y + 6.0, lap + 1, time);
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
 
-    // Write per-lap highlighted DOTs
-    for (i, lap_nodes) in laps.iter().enumerate() {
-        if lap_nodes.len() < 2 { continue; }
-        let mut segs: Vec<(usize, usize)> = Vec::new();
-        for w in lap_nodes.windows(2) { segs.push((w[0], w[1])); }
-        let file = format!("images/{}_lap{}.dot", output_prefix, i + 1);
-        let color = lap_colors.get(i).unwrap_or(&"blue");
-        write_dot(&segs, &[color], &file)?;
-        
-        // Auto-render to SVG if dot is available
-        let svg_file = file.replace(".dot", ".svg");
-        if Command::new("dot").arg("-Tsvg").arg(&file).arg("-o").arg(&svg_file).output().is_ok() {
-            println!("Rendered {}", svg_file.green());
+// Unit vector from `a` to `b`, if they are apart
+fn direction(a: (f64, f64), b: (f64, f64)) -> Option<(f64, f64)> {
+    let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    (length > 1e-9).then(|| ((b.0 - a.0) / length, (b.1 - a.1) / length))
+}
+
+// Largest 1, 2 or 5 times a power of ten not above `length`, for the scale bar
+fn round_length(length: f64) -> f64 {
+    if length <= 0.0 {
+        return 1.0;
+    }
+    let power = 10f64.powf(length.log10().floor());
+    [5.0, 2.0, 1.0].into_iter().map(|m| m * power).find(|&l| l <= length).unwrap_or(power)
+}
+
+// The car's state at one point of the race, for charts against race distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfilePoint {
+    // Distance driven since the start
+    pub distance: Fixed,
+    pub time: Fixed,
+    pub fuel: Fixed,
+    pub tyre_wear: Fixed,
+    pub lap: u32,
+    // Just after a pit stop: refuelled to capacity on fresh tyres
+    pub pit_stop: bool,
+}
+
+// Fuel, tyre wear and time along the race: the start, then the end of every segment, with an
+// extra point after each pit stop so charts show the refuel and tyre change as a jump
+pub fn race_profile(path_info: &PathInfo, car_config: &CarConfig) -> Vec<ProfilePoint> {
+    let mut points = Vec::new();
+    let Some(first) = path_info.segments.first() else {
+        return points;
+    };
+    let mut distance = Fixed::ZERO;
+    points.push(ProfilePoint { distance, time: Fixed::ZERO, fuel: first.fuel_before, tyre_wear: first.tyre_before, lap: first.lap, pit_stop: false });
+    for segment in &path_info.segments {
+        if segment.pit_stop {
+            let time = segment.cumulative_time - segment.time;
+            points.push(ProfilePoint { distance, time, fuel: car_config.fuel_capacity, tyre_wear: Fixed::ZERO, lap: segment.lap, pit_stop: true });
         }
+        distance += segment.distance;
+        points.push(ProfilePoint {
+            distance,
+            time: segment.cumulative_time,
+            fuel: segment.fuel_after,
+            tyre_wear: segment.tyre_after,
+            lap: segment.lap,
+            pit_stop: false,
+        });
     }
+    points
+}
 
-    // Also write a full highlighted version with all lap colors
-    let mut full_segs: Vec<(usize, usize)> = Vec::new();
-    for w in path_info.node_sequence.windows(2) { full_segs.push((w[0], w[1])); }
-    let file = format!("images/{}_full.dot", output_prefix);
-    write_dot(&full_segs, &lap_colors, &file)?;
-    
-    // Auto-render full version to SVG
-    let svg_file = file.replace(".dot", ".svg");
-    if Command::new("dot").arg("-Tsvg").arg(&file).arg("-o").arg(&svg_file).output().is_ok() {
-        println!("Rendered {}", svg_file.green());
+// Terminal colour of a car, used for it throughout the console output
+pub fn car_color(car_id: usize) -> Color {
+    match car_id % 3 {
+        0 => Color::Green,
+        1 => Color::Blue,
+        _ => Color::Magenta,
+    }
+}
+
+// Columns a chart spans in the terminal
+fn chart_width() -> usize {
+    crate::track_map::terminal_width().saturating_sub(14).clamp(20, 100)
+}
+
+// One car's fuel, tyre wear and race time as sparklines against race distance
+pub fn print_race_charts(car_id: usize, path_info: &PathInfo, car_config: &CarConfig) {
+    let profile = race_profile(path_info, car_config);
+    let traces: Vec<(Metric, Trace)> = [Metric::Fuel, Metric::TyreWear, Metric::Time]
+        .into_iter()
+        .map(|metric| (metric, Trace::new(format!("Car {}", car_id + 1), car_color(car_id), GLYPHS[car_id % GLYPHS.len()], &profile, metric)))
+        .collect();
+    println!();
+    for line in charts::sparklines(&traces, chart_width()) {
+        println!("  {}", line);
+    }
+}
+
+// Every car's fuel, tyre wear (with the wear threshold) and race time, overlaid one chart each
+pub fn print_overlay_charts(cars: &[(usize, &PathInfo, &CarConfig)], tyre_wear_distance: Fixed) {
+    let profiles: Vec<(usize, Vec<ProfilePoint>)> = cars.iter().map(|&(i, path_info, car_config)| (i, race_profile(path_info, car_config))).collect();
+    for (metric, threshold) in [(Metric::Fuel, None), (Metric::TyreWear, Some(tyre_wear_distance.to_f64())), (Metric::Time, None)] {
+        let traces: Vec<Trace> = profiles
+            .iter()
+            .map(|(i, profile)| Trace::new(format!("Car {}", i + 1), car_color(*i), GLYPHS[i % GLYPHS.len()], profile, metric))
+            .collect();
+        println!();
+        for line in charts::overlay_chart(metric, &traces, chart_width(), 12, threshold) {
+            println!("  {}", line);
+        }
+    }
+}
+
+pub fn print_pareto_front(car_id: usize, front: &crate::pareto::ParetoFront) {
+    let car_color = car_color(car_id);
+
+    println!("\n{}", format!("=== CAR {} PARETO FRONT ===", car_id + 1).bold().color(car_color));
+    if !front.complete {
+        println!("{}", "Search stopped by a budget; the front may be incomplete".yellow());
+    }
+    if front.points.is_empty() {
+        println!("No feasible strategies.");
+        return;
+    }
+
+    println!("  {:>3}  {:>10}  {:>5}  {:>10}  Path", "#", "Time", "Stops", "Fuel Used");
+    for (i, point) in front.points.iter().enumerate() {
+        let path = point.path_info.node_sequence.iter().map(|n| n.to_string()).collect::<Vec<_>>().join("->");
+        println!("  {:>3}  {:>10}  {:>5}  {:>10}  {}",
+            (i + 1).to_string().color(car_color),
+            point.objectives.total_time,
+            point.objectives.pit_stops,
+            point.objectives.fuel_used,
+            path
+        );
     }
 
-    Ok(())
+    print_pareto_scatter(front);
+}
+
+// Time on the x axis, fuel used on the y axis; each point is drawn as its pit stop count
+fn print_pareto_scatter(front: &crate::pareto::ParetoFront) {
+    const WIDTH: usize = 48;
+    const HEIGHT: usize = 10;
+
+    let times: Vec<f64> = front.points.iter().map(|p| p.objectives.total_time.to_f64()).collect();
+    let fuels: Vec<f64> = front.points.iter().map(|p| p.objectives.fuel_used.to_f64()).collect();
+    let (t_min, t_max) = times.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+    let (f_min, f_max) = fuels.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &f| (lo.min(f), hi.max(f)));
+    let scale = |value: f64, min: f64, max: f64, cells: usize| -> usize {
+        if max > min { (((value - min) / (max - min)) * (cells - 1) as f64).round() as usize } else { 0 }
+    };
+
+    let mut grid = vec![vec![' '; WIDTH]; HEIGHT];
+    for (i, point) in front.points.iter().enumerate() {
+        let x = scale(times[i], t_min, t_max, WIDTH);
+        let y = HEIGHT - 1 - scale(fuels[i], f_min, f_max, HEIGHT);
+        grid[y][x] = std::char::from_digit(point.objectives.pit_stops.min(9) as u32, 10).unwrap_or('+');
+    }
+
+    println!("\n  Fuel used vs. race time (digit = pit stops)");
+    for (row, cells) in grid.iter().enumerate() {
+        let label = if row == 0 {
+            format!("{:>8.1}", f_max)
+        } else if row == HEIGHT - 1 {
+            format!("{:>8.1}", f_min)
+        } else {
+            " ".repeat(8)
+        };
+        println!("  {} |{}", label, cells.iter().collect::<String>().cyan());
+    }
+    println!("  {} +{}", " ".repeat(8), "-".repeat(WIDTH));
+    println!("  {} {:<w$}{:>8.1}", " ".repeat(8), format!("{:.1}", t_min), t_max, w = WIDTH - 8);
 }
@@ -0,0 +1,58 @@
+use f1_track::charts::{self, Metric, Trace};
+mod common;
+
+use f1_track::graph_rcspp::{find_optimal_path, Graph, SolveOptions};
+use f1_track::visual::race_profile;
+use colored::Color;
+
+fn circuit_traces(metric: Metric) -> Vec<Trace> {
+    colored::control::set_override(false);
+    let (race_data, params) = common::circuit();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    race_data
+        .cars
+        .iter()
+        .enumerate()
+        .map(|(i, car)| {
+            let path_info = find_optimal_path(&race_data, &graph, car, &params, &SolveOptions::default()).into_result().unwrap();
+            Trace::new(format!("Car {}", i + 1), Color::Green, charts::GLYPHS[i], &race_profile(&path_info, car), metric)
+        })
+        .collect()
+}
+
+#[test]
+fn value_at_interpolates_and_takes_the_value_after_a_jump() {
+    let points = [(0.0, 100.0), (10.0, 80.0), (10.0, 100.0), (20.0, 90.0)];
+    assert_eq!(charts::value_at(&points, 5.0), 90.0);
+    assert_eq!(charts::value_at(&points, 10.0), 100.0);
+    assert_eq!(charts::value_at(&points, 15.0), 95.0);
+    assert_eq!(charts::value_at(&points, 30.0), 90.0);
+}
+
+#[test]
+fn sparkline_shows_the_refuel_and_marks_the_pit_stop() {
+    let trace = circuit_traces(Metric::Fuel).remove(1);
+    let line = charts::sparkline(&trace, 40, 209.9, 195.0);
+    assert_eq!(line.chars().count(), 40);
+    assert!(line.starts_with('█'), "{}", line);
+    // Fuel runs down to the pit stop, then is back near full
+    let lowest = line.chars().position(|c| c == '▁').unwrap();
+    assert!(line.chars().skip(lowest).any(|c| c == '█'), "{}", line);
+    let markers = charts::marker_row(&trace, 40, 209.9);
+    assert_eq!(markers.trim(), "P");
+    assert!(markers.find('P').unwrap() > lowest.saturating_sub(1));
+}
+
+#[test]
+fn overlay_chart_draws_each_car_with_its_glyph_and_the_threshold() {
+    let traces = circuit_traces(Metric::TyreWear);
+    let chart = charts::overlay_chart(Metric::TyreWear, &traces, 60, 10, Some(100.0));
+    assert_eq!(chart[0], "Tyre wear vs. race distance");
+    let plot = chart[1..11].join("\n");
+    assert!(plot.contains('●') && plot.contains('■'), "{}", plot);
+    // Both cars drive the same first stretch
+    assert!(plot.contains('*'), "{}", plot);
+    assert!(chart.iter().any(|l| l.starts_with("   100.0 ┤╌")), "{:?}", chart);
+    assert!(chart.iter().any(|l| l.trim_start().starts_with("Car 2 ■") && l.ends_with('P')), "{:?}", chart);
+    assert!(chart.iter().any(|l| l.trim_start() == "Car 1 ●"), "{:?}", chart);
+}
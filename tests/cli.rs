@@ -0,0 +1,112 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_f1-track"))
+        .args(args)
+        .arg("--no-color")
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("binary runs")
+}
+
+fn code(args: &[&str]) -> i32 {
+    run(args).status.code().expect("exited normally")
+}
+
+#[test]
+fn validate_reports_whether_cars_can_finish() {
+    assert_eq!(code(&["validate", "examples/simple_track.txt"]), 0);
+    let oval = run(&["validate", "examples/oval_track.txt"]);
+    assert_eq!(oval.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&oval.stdout).contains("Car 2: cannot finish"));
+    let endless = run(&["validate", "examples/simple_track.txt", "--laps", "4000000000"]);
+    assert_eq!(endless.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&endless.stdout).contains("too long to check"));
+}
+
+#[test]
+fn exit_codes_distinguish_failures() {
+    assert_eq!(code(&["solve", "examples/simple_track.txt"]), 0);
+    assert_eq!(code(&["solve", "examples/oval_track.txt"]), 1);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--bogus"]), 2);
+    assert_eq!(code(&["compare", "examples/simple_track.txt", "--json", "out.json"]), 2);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--json", "../out.json"]), 2);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--end", "99"]), 2);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--pit-penalty", "-5"]), 2);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--tyre-wear", "-0.1"]), 2);
+    assert_eq!(code(&["solve", "examples/simple_track.txt", "--pit-penalty", "0", "--tyre-wear", "0"]), 0);
+    assert_eq!(code(&["validate", "examples/does_not_exist.txt"]), 3);
+    assert_eq!(code(&["solve", "examples/complex_track.txt", "--max-labels", "2"]), 4);
+}
+
+#[test]
+fn bare_input_is_solve() {
+    let bare = run(&["examples/simple_track.txt"]);
+    let solve = run(&["solve", "examples/simple_track.txt"]);
+    assert_eq!(bare.status.code(), Some(0));
+    assert_eq!(bare.stdout, solve.stdout);
+}
+
+#[test]
+fn compare_ranks_selected_cars() {
+    let output = run(&["compare", "examples/complex_track.txt", "--cars", "3,1"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The ranking table runs up to the blank line before the lap by lap comparison
+    let table: Vec<&str> = stdout.lines().take_while(|line| !line.is_empty()).collect();
+    let mut cars: Vec<&str> = table.iter().skip(1).map(|line| line.split_whitespace().nth(1).unwrap()).collect();
+    assert!(table[1].starts_with("1 "));
+    cars.sort_unstable();
+    assert_eq!(cars, ["1", "3"]);
+    assert!(stdout.contains("=== STRATEGY COMPARISON ==="));
+}
+
+#[test]
+fn generated_track_validates() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cli_generated.json");
+    let path = path.to_str().unwrap();
+    assert_eq!(code(&["generate", "--nodes", "12", "--seed", "3", "--output", path]), 0);
+    assert_eq!(code(&["validate", path]), 0);
+}
+
+#[test]
+fn json_results_keep_the_selected_cars_numbers() {
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cli_json");
+    let out = out.to_str().unwrap();
+    let args = ["solve", "examples/complex_track.txt", "--cars", "2", "--json", "results.json", "--out", out, "--run-name", "car2", "--overwrite", "replace"];
+    assert_eq!(code(&args), 0);
+    let run = Path::new(out).join("car2");
+    let manifest = std::fs::read_to_string(run.join("manifest.json")).unwrap();
+    assert!(manifest.contains("\"results-json\""), "{}", manifest);
+    let results: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(run.join("results.json")).unwrap()).unwrap();
+    let cars = results["cars"].as_array().unwrap();
+    assert_eq!(cars.len(), 1);
+    assert_eq!(cars[0]["car"], 2);
+    assert_eq!(cars[0]["fuel_capacity"], 80);
+}
+
+#[test]
+fn generate_rejects_values_outside_the_input_limits() {
+    assert_eq!(code(&["generate", "--max-distance", "2000000"]), 2);
+    assert_eq!(code(&["generate", "--car", "-5,2"]), 2);
+    assert_eq!(code(&["generate", "--car", "100,2000000"]), 2);
+    assert_eq!(code(&["generate", "--nodes", "2000000"]), 2);
+    // Shortcuts over the longest edges are capped rather than rejected
+    let at_limit = run(&["generate", "--max-distance", "1000000", "--min-distance", "999999", "--shortcuts", "5"]);
+    assert_eq!(at_limit.status.code(), Some(0));
+}
+
+#[test]
+fn render_writes_into_a_run_folder() {
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cli_render");
+    let _ = std::fs::remove_dir_all(&out);
+    let out = out.to_str().unwrap();
+    assert_eq!(code(&["render", "examples/simple_track.txt", "--out", out, "--run-name", "simple", "--overwrite", "error"]), 0);
+    let run = Path::new(out).join("simple");
+    for file in ["manifest.json", "track.dot", "track.svg", "circuit.svg", "car1_lap1.dot", "car1_lap1.svg", "car1_full.dot", "car1_circuit.svg", "report.html"] {
+        assert!(run.join(file).is_file(), "{} missing", file);
+    }
+    // The same run name again is refused rather than overwritten
+    assert_eq!(code(&["render", "examples/simple_track.txt", "--out", out, "--run-name", "simple", "--overwrite", "error"]), 5);
+}
@@ -0,0 +1,146 @@
+#![allow(dead_code)]
+
+use f1_track::graph_rcspp::{Graph, RaceParams, Resolution};
+use f1_track::input_parser::{parse_input, CarConfig, Edge, RaceData};
+use f1_track::units::Fixed;
+use proptest::prelude::*;
+
+// One lap from node 0 to `end_node`, with the CLI's default pit penalty and tyre wear
+pub fn params(end_node: usize) -> RaceParams {
+    RaceParams {
+        start_node: 0,
+        end_node,
+        total_laps: 1,
+        pit_stop_penalty: Fixed::from_int(60),
+        tyre_wear_distance: Fixed::from_int(100),
+        resolution: Resolution::EXACT,
+    }
+}
+
+// The example circuit, raced once round to its finish at node 7
+pub fn circuit() -> (RaceData, RaceParams) {
+    (parse_input("examples/circuit_track.txt").unwrap(), params(7))
+}
+
+// Exhaustive depth-first search over every strategy, for cross-checking the solver on tiny tracks.
+//
+// Without a pit stop in between, returning to a (lap, node) only burns fuel and wears tyres, and
+// pitting twice at the same (lap, node) restarts from an identical state, so neither can be part of
+// a fastest strategy. Pruning those walks keeps the search finite without losing the optimum.
+pub fn oracle(race_data: &RaceData, graph: &Graph, car_config: &CarConfig, params: &RaceParams) -> Option<Fixed> {
+    let mut search = Oracle { race_data, graph, car_config, params, best: None };
+    let start = (0, params.start_node);
+    search.visit(start, car_config.fuel_capacity, Fixed::ZERO, Fixed::ZERO, &[start], &mut Vec::new());
+    search.best
+}
+
+struct Oracle<'a> {
+    race_data: &'a RaceData,
+    graph: &'a Graph,
+    car_config: &'a CarConfig,
+    params: &'a RaceParams,
+    best: Option<Fixed>,
+}
+
+impl Oracle<'_> {
+    // `since_pit` holds the states visited since the last pit stop, `pitted` every pit stop so far
+    fn visit(&mut self, at: (u32, usize), fuel: Fixed, tyre: Fixed, time: Fixed, since_pit: &[(u32, usize)], pitted: &mut Vec<(u32, usize)>) {
+        if self.best.is_some_and(|best| time >= best) {
+            return;
+        }
+        let (lap, node) = at;
+        if node == self.params.end_node && lap + 1 == self.params.total_laps {
+            self.best = Some(time);
+            return;
+        }
+
+        let params = self.params;
+        let capacity = self.car_config.fuel_capacity;
+        let can_pit = self.race_data.pit_nodes.contains(&node) && !pitted.contains(&at);
+        for &(next, distance) in &self.graph.adj[&node] {
+            let next_lap = if next == params.start_node && node != params.start_node { lap + 1 } else { lap };
+            if next_lap >= params.total_laps {
+                continue;
+            }
+            let to = (next_lap, next);
+            for pit in [false, true] {
+                let (departure_fuel, departure_tyre) = match pit {
+                    false => (fuel, tyre),
+                    true if can_pit => (capacity, Fixed::ZERO),
+                    true => continue,
+                };
+                if departure_fuel < distance {
+                    continue;
+                }
+                let mut visited = if pit { vec![at] } else { since_pit.to_vec() };
+                if visited.contains(&to) {
+                    continue;
+                }
+                visited.push(to);
+
+                let new_tyre = departure_tyre + distance;
+                let penalty = if new_tyre > params.tyre_wear_distance {
+                    (new_tyre - params.tyre_wear_distance) * self.car_config.tyre_cost
+                } else {
+                    Fixed::ZERO
+                };
+                let new_time = time + distance + penalty + if pit { params.pit_stop_penalty } else { Fixed::ZERO };
+                if pit {
+                    pitted.push(at);
+                }
+                self.visit(to, departure_fuel - distance, new_tyre, new_time, &visited, pitted);
+                if pit {
+                    pitted.pop();
+                }
+            }
+        }
+    }
+}
+
+// Values with up to one decimal, so the fixed-point arithmetic gets exercised too
+pub fn tenths(range: std::ops::RangeInclusive<i64>) -> impl Strategy<Value = Fixed> {
+    range.prop_map(|tenths| Fixed::from_raw(tenths * 100))
+}
+
+// A tiny track with strictly positive edge distances and no cars
+pub fn track() -> impl Strategy<Value = RaceData> {
+    (2usize..=5).prop_flat_map(|n| {
+        let edge = (0..n, 0..n, tenths(5..=300)).prop_map(|(u, v, distance)| Edge { u, v, distance });
+        (Just(n), prop::collection::vec(edge, n..=3 * n), prop::collection::btree_set(0..n, 0..=2))
+    })
+    .prop_map(|(n, edges, pit_nodes)| RaceData {
+        n,
+        np: pit_nodes.len(),
+        c: 0,
+        m: edges.len(),
+        edges,
+        pit_nodes: pit_nodes.into_iter().collect(),
+        cars: Vec::new(),
+        positions: None,
+    })
+}
+
+pub fn car() -> impl Strategy<Value = CarConfig> {
+    (tenths(50..=800), tenths(0..=30)).prop_map(|(fuel_capacity, tyre_cost)| CarConfig { fuel_capacity, tyre_cost })
+}
+
+pub fn race_params(n: usize) -> impl Strategy<Value = RaceParams> {
+    (0..n, 0..n, 1u32..=2, tenths(0..=400), tenths(0..=600)).prop_map(
+        |(start_node, end_node, total_laps, pit_stop_penalty, tyre_wear_distance)| RaceParams {
+            start_node,
+            end_node,
+            total_laps,
+            pit_stop_penalty,
+            tyre_wear_distance,
+            resolution: Resolution::EXACT,
+        },
+    )
+}
+
+// A track together with one car and race parameters that fit it
+pub fn scenario() -> impl Strategy<Value = (RaceData, CarConfig, RaceParams)> {
+    track().prop_flat_map(|race_data| {
+        let n = race_data.n;
+        (Just(race_data), car(), race_params(n))
+    })
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use f1_track::comparison::{compare_strategies, describe, Route};
+use f1_track::graph_rcspp::{solve_all_with, Graph, PathInfo, Segment, SolveOptions};
+use f1_track::input_parser::parse_input;
+use f1_track::units::Fixed;
+use f1_track::{report, visual};
+
+fn strategies(path: &str, end_node: usize) -> Vec<(usize, PathInfo)> {
+    let race_data = parse_input(path).unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let params = common::params(end_node);
+    solve_all_with(&race_data, &graph, &SolveOptions::default(), |_| params)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(car, result)| result.ok().and_then(|outcome| outcome.best).map(|best| (car, best)))
+        .collect()
+}
+
+// A segment taking `time`, `pit` of it in the pit lane and `tyre` of it in tyre penalty
+fn segment(lap: u32, from: usize, to: usize, time: i64, pit: i64, tyre: i64) -> Segment {
+    let time = Fixed::from_int(time);
+    let tyre_penalty = Fixed::from_int(tyre);
+    Segment {
+        lap,
+        from,
+        to,
+        pit_stop: pit > 0,
+        distance: time - Fixed::from_int(pit) - tyre_penalty,
+        time,
+        cumulative_time: Fixed::ZERO,
+        fuel_before: Fixed::ZERO,
+        fuel_after: Fixed::ZERO,
+        tyre_before: Fixed::ZERO,
+        tyre_after: Fixed::ZERO,
+        tyre_penalty,
+    }
+}
+
+fn path(segments: Vec<Segment>) -> PathInfo {
+    let mut node_sequence: Vec<usize> = segments.iter().map(|s| s.from).collect();
+    node_sequence.extend(segments.last().map(|s| s.to));
+    let pit_stops = segments.iter().filter(|s| s.pit_stop).map(|s| (s.lap, s.from)).collect();
+    PathInfo { total_time: segments.iter().map(|s| s.time).sum(), pit_stops, node_sequence, segments }
+}
+
+#[test]
+fn needs_two_strategies() {
+    let only = path(vec![segment(0, 0, 1, 10, 0, 0)]);
+    assert!(compare_strategies(&[]).is_none());
+    assert!(compare_strategies(&[(0, &only)]).is_none());
+}
+
+#[test]
+fn gap_is_split_into_segments() {
+    let cars = strategies("examples/circuit_track.txt", 7);
+    let refs: Vec<(usize, &PathInfo)> = cars.iter().map(|(car, p)| (*car, p)).collect();
+    let comparison = compare_strategies(&refs).unwrap();
+    // Car 2 pits but is faster, so car 1 is measured against it
+    assert_eq!(comparison.reference, 1);
+    assert_eq!(comparison.reference_time, cars[1].1.total_time);
+    let car = &comparison.cars[0];
+    assert_eq!(car.car, 0);
+    assert_eq!(car.gap, cars[0].1.total_time - cars[1].1.total_time);
+    assert_eq!(car.laps.len(), 1);
+    assert!(car.laps[0].pits_differ());
+    assert_eq!(car.laps[0].reference_pits, vec![6]);
+    assert_eq!(car.laps[0].gap, car.gap);
+
+    let sum: Fixed = car.contributions.iter().map(|c| c.delta).sum();
+    assert_eq!(sum, car.gap);
+    for pair in car.contributions.windows(2) {
+        assert!(pair[0].delta.raw().abs() >= pair[1].delta.raw().abs());
+    }
+    // Car 1 skips the pit lane car 2 drives through
+    let pit_lane = car.contributions.iter().find(|c| c.from == 6).unwrap();
+    assert_eq!(pit_lane.route, Route::OnlyReference);
+    assert_eq!(pit_lane.pit, Fixed::ZERO - Fixed::from_int(60));
+    assert_eq!(describe(pit_lane, 0, 1), "only car 2 drives it, pit stop -60");
+}
+
+#[test]
+fn gap_evolves_lap_by_lap() {
+    // Car 1 is ahead after lap 1, then loses it all pitting on lap 2
+    let reference = path(vec![segment(0, 0, 1, 30, 0, 0), segment(0, 1, 0, 30, 0, 0), segment(1, 0, 1, 30, 0, 0), segment(1, 1, 0, 35, 0, 5)]);
+    let car = path(vec![segment(0, 0, 1, 25, 0, 0), segment(0, 1, 0, 30, 0, 0), segment(1, 0, 2, 90, 60, 0), segment(1, 2, 0, 10, 0, 0)]);
+    let comparison = compare_strategies(&[(0, &car), (1, &reference)]).unwrap();
+    assert_eq!(comparison.reference, 1);
+    let car = &comparison.cars[0];
+    let gaps: Vec<Fixed> = car.laps.iter().map(|l| l.gap).collect();
+    assert_eq!(gaps, vec![Fixed::ZERO - Fixed::from_int(5), Fixed::from_int(30)]);
+    assert!(!car.laps[0].pits_differ());
+    assert!(car.laps[1].pits_differ());
+    assert_eq!(car.laps[1].pits, vec![0]);
+
+    // Identical segments drop out; the rest add up to the gap
+    let deltas: Vec<(u32, usize, usize, Route, Fixed)> = car.contributions.iter().map(|c| (c.lap, c.from, c.to, c.route, c.delta)).collect();
+    assert_eq!(deltas, vec![
+        (1, 0, 2, Route::OnlyCar, Fixed::from_int(90)),
+        (1, 1, 0, Route::OnlyReference, Fixed::ZERO - Fixed::from_int(35)),
+        (1, 0, 1, Route::OnlyReference, Fixed::ZERO - Fixed::from_int(30)),
+        (1, 2, 0, Route::OnlyCar, Fixed::from_int(10)),
+        (0, 0, 1, Route::Both, Fixed::ZERO - Fixed::from_int(5)),
+    ]);
+}
+
+#[test]
+fn terminal_view_lines_up_laps() {
+    colored::control::set_override(false);
+    let cars = strategies("examples/circuit_track.txt", 7);
+    let refs: Vec<(usize, &PathInfo)> = cars.iter().map(|(car, p)| (*car, p)).collect();
+    let text = visual::format_comparison(&compare_strategies(&refs).unwrap());
+    assert!(text.contains("=== STRATEGY COMPARISON ==="));
+    assert!(text.contains("Against car 2, the fastest at 452.7 units"));
+    assert!(text.contains("1      452.7 P6               588.2 +135.5 gap +135.5"));
+    assert!(text.contains("Laps in yellow pit differently from the fastest car"));
+    assert!(text.contains("Where car 1 loses 135.5 units to car 2:"));
+    assert!(text.contains("only car 2 drives it, pit stop -60"));
+    assert!(text.lines().all(|l| l == l.trim_end()));
+}
+
+#[test]
+fn tied_cars_have_nothing_to_explain() {
+    colored::control::set_override(false);
+    let cars = strategies("examples/complex_track.txt", 5);
+    let refs: Vec<(usize, &PathInfo)> = cars.iter().map(|(car, p)| (*car, p)).collect();
+    let comparison = compare_strategies(&refs).unwrap();
+    let tied = comparison.cars.iter().find(|c| c.gap == Fixed::ZERO).unwrap();
+    assert!(tied.contributions.iter().map(|c| c.delta).sum::<Fixed>() == Fixed::ZERO);
+    let text = visual::format_comparison(&comparison);
+    assert!(text.contains(&format!("Where car {} ties with car {}:", tied.car + 1, comparison.reference + 1)));
+}
+
+#[test]
+fn gap_chart_reaches_below_zero() {
+    let series = [report::Series { name: "Car 1".to_string(), color: report::car_color(0), points: vec![(0.0, 0.0), (1.0, -5.0), (2.0, 30.0)] }];
+    let svg = report::line_chart("Gap to car 2", "lap", "gap", &series, &[], None);
+    assert!(svg.contains(">lap</text>"));
+    assert!(svg.contains(">-10</text>"));
+    assert!(svg.contains(">30</text>"));
+    // Whole laps only on the x axis
+    assert!(svg.contains(">2</text>"));
+    assert!(!svg.contains(">0.5</text>"));
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use f1_track::diagnosis::{can_finish, diagnose, Cause};
+use f1_track::graph_rcspp::{Graph, RaceParams};
+use f1_track::input_parser::parse_str;
+
+fn params(end_node: usize, total_laps: u32) -> RaceParams {
+    RaceParams { total_laps, ..common::params(end_node) }
+}
+
+#[test]
+fn unreachable_finish_lists_the_final_lap() {
+    // Node 3 is easy to reach on the first lap, but nothing leads back to the start for a second
+    let race_data = parse_str("4\n0\n1\n3\n0 1 10\n1 2 10\n2 3 10\n100 1\n").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let diagnosis = diagnose(&race_data, &graph, &race_data.cars[0], &params(3, 3));
+    assert_eq!(diagnosis.causes, vec![Cause::FinishUnreachable { start: 0, end: 3, laps: 3, reachable: vec![] }]);
+    assert_eq!(
+        diagnosis.causes[0].to_string(),
+        "node 3 cannot be reached from node 0 over 3 lap(s); the car never gets back to node 0 to start lap 3"
+    );
+
+    // Going round is possible, but the finish lies off the circuit
+    let race_data = parse_str("4\n0\n1\n3\n0 1 10\n1 0 10\n3 0 10\n100 1\n").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let diagnosis = diagnose(&race_data, &graph, &race_data.cars[0], &params(3, 2));
+    assert_eq!(
+        diagnosis.causes[0].to_string(),
+        "node 3 cannot be reached from node 0 over 2 lap(s); nodes reachable on lap 2: [0, 1]"
+    );
+}
+
+#[test]
+fn races_too_long_to_unroll_are_reported_not_allocated() {
+    let race_data = parse_str("4\n0\n1\n4\n0 1 10\n1 2 10\n2 3 10\n3 0 10\n100 1\n").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let car = &race_data.cars[0];
+    let diagnosis = diagnose(&race_data, &graph, car, &params(3, 4_000_000_000));
+    assert_eq!(diagnosis.causes, vec![Cause::TooManyLaps { laps: 4_000_000_000, nodes: 4 }]);
+    assert_eq!(can_finish(&race_data, &graph, car, &params(3, 4_000_000_000)), None);
+    assert_eq!(can_finish(&race_data, &graph, car, &params(3, 2)), Some(true));
+}
@@ -0,0 +1,81 @@
+// Snapshot tests: every track in examples/ is parsed, solved and exported, and each artefact is
+// compared with the committed copy under tests/golden/<example>/.
+// Run with UPDATE_GOLDEN=1 to write new snapshots after an intended output change.
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use f1_track::graph_rcspp::{solve_all, Graph, SolveOptions};
+use f1_track::input_parser::parse_input;
+use f1_track::{json, visual};
+
+// Every artefact produced for one example, by file name
+fn artefacts(example: &Path) -> Vec<(String, String)> {
+    let race_data = parse_input(example.to_str().unwrap()).expect("example parses");
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let results = solve_all(&race_data, &graph, &common::params(race_data.n - 1), &SolveOptions::default());
+
+    let mut files = vec![("track.dot".to_string(), visual::track_dot(&race_data))];
+    let mut strategies = String::new();
+    for (i, result) in results.iter().enumerate() {
+        match result {
+            Ok(path_info) => {
+                strategies.push_str(&visual::format_race_strategy(i, path_info, &race_data));
+                files.push((format!("car{}_full.dot", i + 1), visual::highlighted_dot(&race_data, &path_info.segments, &visual::LAP_COLORS)));
+                if race_data.positions.is_some() {
+                    let map = visual::circuit_svg(&race_data, 0, race_data.n - 1, Some(path_info));
+                    files.push((format!("car{}_circuit.svg", i + 1), map));
+                }
+            }
+            Err(e) => strategies.push_str(&format!("\n=== CAR {} ===\n{}\n", i + 1, e)),
+        }
+    }
+    files.push(("strategy.txt".to_string(), strategies));
+    files.push(("results.json".to_string(), json::results_json(&race_data, &results.into_iter().enumerate().collect::<Vec<_>>())));
+    files
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    let line = expected.lines().zip(actual.lines()).position(|(e, a)| e != a).unwrap_or(expected.lines().count().min(actual.lines().count()));
+    format!(
+        "first difference at line {}:\n  expected: {:?}\n  actual:   {:?}",
+        line + 1,
+        expected.lines().nth(line).unwrap_or("<end of file>"),
+        actual.lines().nth(line).unwrap_or("<end of file>")
+    )
+}
+
+#[test]
+fn examples_match_golden_files() {
+    colored::control::set_override(false);
+    let bless = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let mut examples: Vec<PathBuf> = fs::read_dir(root.join("examples"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    examples.sort();
+    assert!(!examples.is_empty());
+
+    let mut failures = Vec::new();
+    for example in &examples {
+        let dir = root.join("tests/golden").join(example.file_stem().unwrap());
+        for (name, actual) in artefacts(example) {
+            let golden = dir.join(&name);
+            if bless {
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(&golden, &actual).unwrap();
+                continue;
+            }
+            match fs::read_to_string(&golden) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => failures.push(format!("{} differs; {}", golden.display(), first_difference(&expected, &actual))),
+                Err(_) => failures.push(format!("{} is missing", golden.display())),
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}\nRun with UPDATE_GOLDEN=1 to accept the new output.", failures.join("\n"));
+}
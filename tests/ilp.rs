@@ -0,0 +1,40 @@
+mod common;
+
+use f1_track::graph_rcspp::{find_optimal_path, Graph, RaceParams, SolveOptions};
+use f1_track::ilp::{import_solution, write_lp};
+use f1_track::input_parser::parse_input;
+
+#[test]
+fn solver_path_round_trips_through_the_lp_model() {
+    let race_data = parse_input("examples/simple_track.txt").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let car = &race_data.cars[0];
+    // Two laps on 50 fuel cannot be driven without a pit stop
+    let params = RaceParams { total_laps: 2, ..common::params(3) };
+    let best = find_optimal_path(&race_data, &graph, car, &params, &SolveOptions::default()).best.unwrap();
+    assert!(!best.pit_stops.is_empty());
+
+    let mut model = Vec::new();
+    write_lp(&race_data, car, &params, &mut model).unwrap();
+    let model = String::from_utf8(model).unwrap();
+
+    // What an LP solver would report for the same strategy: one `x_` per driven edge, one `p_` per stop
+    let mut solution = String::new();
+    for segment in &best.segments {
+        let edge = race_data.edges.iter().position(|e| (e.u, e.v) == (segment.from, segment.to)).unwrap();
+        let name = format!("x_{}_{}", segment.lap, edge);
+        assert!(model.contains(&name), "{} is not in the model", name);
+        solution.push_str(&format!("{} 1\n", name));
+    }
+    for &(lap, node) in &best.pit_stops {
+        let name = format!("p_{}_{}", lap, node);
+        assert!(model.contains(&name), "{} is not in the model", name);
+        solution.push_str(&format!("{} 1\n", name));
+    }
+
+    let imported = import_solution(&race_data, &graph, car, &params, &solution).unwrap();
+    assert_eq!(imported.total_time, best.total_time);
+    assert_eq!(imported.node_sequence, best.node_sequence);
+    assert_eq!(imported.pit_stops, best.pit_stops);
+    assert_eq!(imported.segments, best.segments);
+}
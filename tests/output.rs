@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use f1_track::output::{self, OutputOptions, Overwrite, RunDir};
+
+// A fresh output root per test, so tests can run in parallel
+fn root(name: &str) -> PathBuf {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("output_tests").join(name);
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+fn options(root: &Path, overwrite: Overwrite) -> OutputOptions {
+    OutputOptions { root: root.to_path_buf(), run_name: Some("run".to_string()), overwrite }
+}
+
+#[test]
+fn timestamps_are_utc_calendar_times() {
+    assert_eq!(output::timestamp(0), "19700101-000000");
+    assert_eq!(output::timestamp(951_782_400), "20000229-000000");
+    assert_eq!(output::timestamp(1_792_365_725), "20261018-232205");
+}
+
+#[test]
+fn default_run_folders_are_named_after_the_scenario() {
+    let root = root("default_name");
+    let run = RunDir::create(&OutputOptions { root: root.clone(), ..OutputOptions::default() }, "tracks/my track.txt", "render").unwrap();
+    let name = run.path().file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("my_track-"), "{}", name);
+    assert_eq!(name.len(), "my_track-".len() + "YYYYMMDD-HHMMSS".len());
+}
+
+#[test]
+fn manifest_lists_every_artefact() {
+    let root = root("manifest");
+    let mut run = RunDir::create(&options(&root, Overwrite::Error), "oval.txt", "render").unwrap();
+    run.write("track.dot", "digraph {}", "track-dot", None, None).unwrap();
+    run.write("car1_lap1.dot", "digraph {}", "strategy-dot", Some(1), Some(1)).unwrap();
+    // Writing the same file again replaces its entry
+    run.write("track.dot", "digraph { 0 }", "track-dot", None, None).unwrap();
+
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(run.path().join(output::MANIFEST)).unwrap()).unwrap();
+    assert_eq!(manifest["scenario"], "oval");
+    assert_eq!(manifest["command"], "render");
+    let artefacts = manifest["artefacts"].as_array().unwrap();
+    assert_eq!(artefacts.len(), 2);
+    assert_eq!(artefacts[0]["path"], "car1_lap1.dot");
+    assert_eq!(artefacts[0]["car"], 1);
+    assert_eq!(artefacts[0]["lap"], 1);
+    assert_eq!(artefacts[1]["path"], "track.dot");
+    assert!(artefacts[1].get("car").is_none());
+}
+
+#[test]
+fn overwrite_policies() {
+    let root = root("overwrite");
+    let mut first = RunDir::create(&options(&root, Overwrite::Error), "t.txt", "render").unwrap();
+    first.write("old.dot", "", "track-dot", None, None).unwrap();
+
+    assert!(RunDir::create(&options(&root, Overwrite::Error), "t.txt", "render").is_err());
+
+    let second = RunDir::create(&options(&root, Overwrite::Increment), "t.txt", "render").unwrap();
+    assert_eq!(second.path(), root.join("run-2"));
+    let third = RunDir::create(&options(&root, Overwrite::Increment), "t.txt", "render").unwrap();
+    assert_eq!(third.path(), root.join("run-3"));
+
+    let replaced = RunDir::create(&options(&root, Overwrite::Replace), "t.txt", "render").unwrap();
+    assert_eq!(replaced.path(), root.join("run"));
+    assert!(!root.join("run/old.dot").exists());
+}
+
+#[test]
+fn replace_only_deletes_previous_runs() {
+    let root = root("replace_guard");
+    fs::create_dir_all(root.join("run")).unwrap();
+    fs::write(root.join("run/notes.txt"), "keep me").unwrap();
+    assert!(RunDir::create(&options(&root, Overwrite::Replace), "t.txt", "render").is_err());
+    assert!(root.join("run/notes.txt").exists());
+
+    let parent = OutputOptions { run_name: Some("..".to_string()), ..options(&root, Overwrite::Replace) };
+    assert!(RunDir::create(&parent, "t.txt", "render").is_err());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use f1_track::bidirectional::find_optimal_path_bidirectional;
+use f1_track::{diagnosis, verify};
+use f1_track::generator::{generate, PitPlacement, Shape, TrackSpec};
+use f1_track::graph_rcspp::{find_optimal_path, Graph, RaceParams, SolveOptions};
+use f1_track::input_parser::{self, CarConfig};
+use f1_track::pareto::{self, Objectives};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn solver_matches_oracle((race_data, car, params) in common::scenario()) {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let solved = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default());
+        let expected = common::oracle(&race_data, &graph, &car, &params);
+        prop_assert_eq!(solved.best.map(|p| p.total_time), expected);
+    }
+
+    #[test]
+    fn bidirectional_matches_solver((race_data, car, params) in common::scenario()) {
+        // The bidirectional search only handles single-lap races itself
+        let params = RaceParams { total_laps: 1, ..params };
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let uni = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default());
+        let bi = find_optimal_path_bidirectional(&race_data, &graph, &car, &params, &SolveOptions::default());
+        prop_assert_eq!(bi.status, uni.status);
+        prop_assert_eq!(bi.best.as_ref().map(|p| p.total_time), uni.best.as_ref().map(|p| p.total_time));
+        if let Some(best) = &bi.best {
+            prop_assert!(verify::verify_strategy(&race_data, &graph, &car, &params, best).is_ok());
+        }
+    }
+
+    #[test]
+    fn more_fuel_is_never_slower((race_data, car, params) in common::scenario(), extra in common::tenths(1..=300)) {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let bigger = CarConfig { fuel_capacity: car.fuel_capacity + extra, tyre_cost: car.tyre_cost };
+        let base = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default()).best;
+        let more = find_optimal_path(&race_data, &graph, &bigger, &params, &SolveOptions::default()).best;
+        if let Some(base) = base {
+            prop_assert!(more.as_ref().is_some_and(|more| more.total_time <= base.total_time));
+        }
+    }
+
+    #[test]
+    fn extra_pit_node_never_hurts((race_data, car, params) in common::scenario(), pit in 0usize..5) {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let pit = pit % race_data.n;
+        let mut with_pit = race_data.clone();
+        if !with_pit.pit_nodes.contains(&pit) {
+            with_pit.pit_nodes.push(pit);
+            with_pit.np += 1;
+        }
+        let base = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default()).best;
+        let more = find_optimal_path(&with_pit, &graph, &car, &params, &SolveOptions::default()).best;
+        if let Some(base) = base {
+            prop_assert!(more.as_ref().is_some_and(|more| more.total_time <= base.total_time));
+        }
+    }
+
+    #[test]
+    fn pareto_front_is_sound((race_data, car, params) in common::scenario()) {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let front = pareto::find_pareto_front(&race_data, &graph, &car, &params, &SolveOptions::default());
+        prop_assert!(front.complete);
+        let fastest = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default()).best;
+        prop_assert_eq!(front.points.first().map(|p| p.objectives.total_time), fastest.map(|p| p.total_time));
+        for (i, a) in front.points.iter().enumerate() {
+            prop_assert!(verify::verify_strategy(&race_data, &graph, &car, &params, &a.path_info).is_ok());
+            prop_assert_eq!(a.objectives.total_time, a.path_info.total_time);
+            prop_assert_eq!(a.objectives.pit_stops, a.path_info.pit_stops.len());
+            for b in &front.points[i + 1..] {
+                let (a, b) = (a.objectives, b.objectives);
+                let covers = |x: Objectives, y: Objectives| x.total_time <= y.total_time && x.pit_stops <= y.pit_stops && x.fuel_used <= y.fuel_used;
+                prop_assert!(!covers(a, b) && !covers(b, a), "{:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn can_finish_matches_solver((race_data, car, params) in common::scenario()) {
+        let graph = Graph::new(race_data.n, &race_data.edges);
+        let solved = find_optimal_path(&race_data, &graph, &car, &params, &SolveOptions::default());
+        prop_assert_eq!(diagnosis::can_finish(&race_data, &graph, &car, &params), Some(solved.best.is_some()));
+    }
+
+    #[test]
+    fn generated_tracks_round_trip(
+        nodes in 2usize..40,
+        shape in prop_oneof![Just(Shape::Ring), Just(Shape::Grid), Just(Shape::Circuit)],
+        pit_placement in prop_oneof![Just(PitPlacement::Spaced), Just(PitPlacement::Random), Just(PitPlacement::Lane)],
+        shortcuts in 0usize..6,
+        pits in 0usize..6,
+        seed: u64,
+    ) {
+        let spec = TrackSpec { nodes, shape, shortcuts, pits, pit_placement, seed, ..TrackSpec::default() };
+        let race_data = generate(&spec).unwrap();
+        prop_assert_eq!(race_data.n, nodes);
+
+        let from_text = input_parser::parse_str(&input_parser::to_text(&race_data)).unwrap();
+        let from_json = input_parser::parse_json_str(&input_parser::to_json(&race_data)).unwrap();
+        for parsed in [from_text, from_json] {
+            prop_assert_eq!(input_parser::to_text(&parsed), input_parser::to_text(&race_data));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::io;
+use f1_track::graph_rcspp::{find_optimal_path, Graph, RaceParams, Resolution, SolveOptions};
+use f1_track::input_parser::{self, parse_input};
+use f1_track::render::{self, attr, Engine, ImageFormat};
+use f1_track::units::Fixed;
+use f1_track::visual;
+
+#[test]
+fn attribute_values_are_quoted_only_when_needed() {
+    let graph = render::digraph(vec![
+        render::node(0, vec![attr("label", "P0"), attr("penwidth", "3.0")]),
+        render::edge(0, 1, vec![attr("label", "10\\nt +10"), attr("color", "gray80")]),
+    ]);
+    let dot = render::to_dot(&graph);
+    assert!(dot.starts_with("digraph Track {\n"), "{}", dot);
+    assert!(dot.contains("0[label=P0,penwidth=3.0];"), "{}", dot);
+    assert!(dot.contains("0 -> 1 [label=\"10\\nt +10\",color=gray80];"), "{}", dot);
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn builtin_svg_draws_every_node_and_edge() {
+    let race_data = parse_input("examples/pit_stop_track.txt").unwrap();
+    let svg = render::builtin_svg(&visual::track_graph(&race_data));
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    // Three plain nodes as circles, the pit node as a box
+    assert_eq!(svg.matches("<circle").count(), 3);
+    assert_eq!(svg.matches("<rect x=").count(), 1);
+    assert!(svg.contains(">P1</tspan>"));
+    assert_eq!(svg.matches("<path").count(), race_data.edges.len());
+    assert_eq!(svg.matches("<polygon").count(), race_data.edges.len());
+    for distance in ["50", "40", "30"] {
+        assert!(svg.contains(&format!(">{}</tspan>", distance)), "{}", distance);
+    }
+}
+
+#[test]
+fn builtin_svg_splits_and_escapes_labels() {
+    let graph = render::digraph(vec![
+        render::edge(0, 1, vec![attr("label", "a < b\\nline 2"), attr("color", "gray80"), attr("style", "dashed")]),
+        render::edge(1, 0, vec![attr("label", "back")]),
+    ]);
+    let svg = render::builtin_svg(&graph);
+    assert!(svg.contains(">a &lt; b</tspan><tspan"), "{}", svg);
+    assert!(svg.contains(">line 2</tspan>"), "{}", svg);
+    // Graphviz grey levels become RGB, which SVG understands
+    assert!(svg.contains("stroke=\"#cccccc\""), "{}", svg);
+    assert!(svg.contains("stroke-dasharray"), "{}", svg);
+    // Opposite edges bow apart instead of overlapping
+    let paths: Vec<&str> = svg.lines().filter(|l| l.starts_with("<path")).collect();
+    assert_eq!(paths.len(), 2);
+    let control = |path: &str| path.split(" Q ").nth(1).unwrap().split(' ').take(2).collect::<Vec<_>>().join(" ");
+    assert_ne!(control(paths[0]), control(paths[1]), "{:?}", paths);
+}
+
+#[test]
+fn renderers_report_which_one_drew_the_image() {
+    let graph = visual::track_graph(&parse_input("examples/simple_track.txt").unwrap());
+
+    let (svg, engine) = render::render(&graph, ImageFormat::Svg, Engine::Builtin).unwrap();
+    assert_eq!(engine, Engine::Builtin);
+    assert!(String::from_utf8(svg).unwrap().contains("<svg"));
+    let png = render::render(&graph, ImageFormat::Png, Engine::Builtin).unwrap_err();
+    assert_eq!(png.kind(), io::ErrorKind::Unsupported);
+
+    // Whether Graphviz is installed decides the rest, so both outcomes are checked
+    match render::render(&graph, ImageFormat::Svg, Engine::Graphviz) {
+        Ok((_, engine)) => {
+            assert_eq!(engine, Engine::Graphviz);
+            assert_eq!(render::render(&graph, ImageFormat::Svg, Engine::Auto).unwrap().1, Engine::Graphviz);
+        }
+        Err(e) => {
+            assert_eq!(e.kind(), io::ErrorKind::NotFound);
+            assert_eq!(render::render(&graph, ImageFormat::Svg, Engine::Auto).unwrap().1, Engine::Builtin);
+        }
+    }
+}
+
+#[test]
+fn image_options_parse() {
+    assert_eq!("png".parse::<ImageFormat>(), Ok(ImageFormat::Png));
+    assert!("gif".parse::<ImageFormat>().is_err());
+    assert_eq!("builtin".parse::<Engine>(), Ok(Engine::Builtin));
+    assert!("cairo".parse::<Engine>().is_err());
+}
+
+#[test]
+fn positions_are_optional_in_both_formats() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let positions = race_data.positions.as_ref().unwrap();
+    assert_eq!(positions.len(), race_data.n);
+    assert_eq!((positions[6].x, positions[6].y), (Fixed::from_int(-10), Fixed::from_int(40)));
+    let json = input_parser::to_json(&race_data);
+    assert!(json.contains("\"positions\""));
+    assert_eq!(input_parser::parse_json_str(&json).unwrap().positions, race_data.positions);
+
+    assert!(parse_input("examples/simple_track.txt").unwrap().positions.is_none());
+    // A position section must cover every node exactly once
+    let text = input_parser::to_text(&race_data);
+    let short: Vec<&str> = text.lines().take(text.lines().count() - 1).collect();
+    assert!(input_parser::parse_str(&short.join("\n")).is_err());
+    assert!(input_parser::parse_str(&format!("{}1 1\n", text)).is_err());
+
+    // Text after the cars is only read as positions under a `positions` line
+    let simple = std::fs::read_to_string("examples/simple_track.txt").unwrap();
+    let noted = input_parser::parse_str(&format!("{}\nWet qualifying, 12 5 laps\n", simple)).unwrap();
+    assert!(noted.positions.is_none());
+    let unmarked = text.replace("positions\n", "");
+    assert!(input_parser::parse_str(&unmarked).unwrap().positions.is_none());
+}
+
+#[test]
+fn circuit_map_shows_the_track_and_strategy() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    // Node 6 is a pit lane around the 5 -> 7 edge
+    assert_eq!(visual::pit_lane_edges(&race_data).into_iter().collect::<Vec<_>>(), [(5, 6), (6, 7)]);
+
+    let map = visual::circuit_svg(&race_data, 0, 7, None);
+    assert!(map.contains(">START<") && map.contains(">FINISH<"));
+    assert!(!map.contains("not to scale"));
+    assert!(!map.contains("PIT lap"));
+
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let params = RaceParams {
+        start_node: 0,
+        end_node: 7,
+        total_laps: 1,
+        pit_stop_penalty: Fixed::from_int(60),
+        tyre_wear_distance: Fixed::from_int(100),
+        resolution: Resolution::EXACT,
+    };
+    let path_info = find_optimal_path(&race_data, &graph, &race_data.cars[1], &params, &SolveOptions::default()).into_result().unwrap();
+    let map = visual::circuit_svg(&race_data, 0, 0, Some(&path_info));
+    assert!(map.contains(">START/FINISH<"));
+    assert!(map.contains(">PIT lap 1<"));
+    assert!(map.contains(&format!(">Lap 1: {}<", path_info.total_time)));
+    assert_eq!(map.matches("stroke=\"blue\" stroke-width=\"3\" stroke-linecap").count(), path_info.segments.len());
+}
+
+#[test]
+fn circuit_map_without_positions_says_so() {
+    let map = visual::circuit_svg(&parse_input("examples/simple_track.txt").unwrap(), 0, 3, None);
+    assert!(map.contains("not to scale"));
+}
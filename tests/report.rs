@@ -0,0 +1,80 @@
+mod common;
+
+use common::params;
+use f1_track::graph_rcspp::{solve_all_with, Graph, RaceParams, SolveError, SolveOptions, SolveOutcome};
+use f1_track::input_parser::{parse_input, RaceData};
+use f1_track::units::Fixed;
+use f1_track::{report, visual};
+
+fn solve(race_data: &RaceData, params: &RaceParams) -> Vec<(usize, Result<SolveOutcome, SolveError>)> {
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    solve_all_with(race_data, &graph, &SolveOptions::default(), |_| *params).into_iter().enumerate().collect()
+}
+
+#[test]
+fn report_is_self_contained() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let params = params(race_data.n - 1);
+    let html = report::html_report("circuit_track", &race_data, &params, &solve(&race_data, &params));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.trim_end().ends_with("</html>"));
+    // No scripts, stylesheets or images fetched from elsewhere
+    for external in ["src=", "href=", "<script", "@import", "url("] {
+        assert!(!html.contains(external), "{}", external);
+    }
+    assert_eq!(html.matches("http").count(), html.matches("xmlns=\"http://www.w3.org/2000/svg\"").count());
+}
+
+#[test]
+fn report_covers_every_car() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let params = params(race_data.n - 1);
+    let cars = solve(&race_data, &params);
+    let html = report::html_report("circuit_track", &race_data, &params, &cars);
+    assert!(html.contains("<title>Race report: circuit_track</title>"));
+    for (car, result) in &cars {
+        let best = result.as_ref().unwrap().best.as_ref().unwrap();
+        assert!(html.contains(&format!("<section id=\"car{}\">", car + 1)));
+        assert!(html.contains(&format!("Total race time: <b>{}</b>", best.total_time)));
+    }
+    // Car 2 is faster, so it ranks first in the comparison
+    let first_row = html.lines().find(|l| l.starts_with("<tr><td>1</td>")).unwrap();
+    assert!(first_row.contains("<b>2</b>"), "{}", first_row);
+    assert!(html.contains("Pit stops: lap 1 at node 6"));
+    assert!(html.contains("<summary>Lap 1 segments</summary>"));
+    assert!(html.contains("<tr class=\"pit\">"));
+    // Car 1 is lined up lap by lap against car 2, which pits where it does not
+    assert!(html.contains("<h3>Lap by lap against car 2</h3>"));
+    assert!(html.contains("<td class=\"left differ\">-</td>"));
+    assert!(html.contains("<h3>Where car 1 loses 135.5 to car 2</h3>"));
+    assert!(html.contains("only car 2 drives it, pit stop -60"));
+    // Track map, two strategy maps, four comparison charts and two charts per car
+    assert_eq!(html.matches("<svg").count(), 1 + 2 + 4 + 2 * 2);
+}
+
+#[test]
+fn report_explains_cars_without_a_strategy() {
+    let race_data = parse_input("examples/oval_track.txt").unwrap();
+    let params = params(race_data.n - 1);
+    let cars = solve(&race_data, &params);
+    let html = report::html_report("oval_track", &race_data, &params, &cars);
+    assert!(cars.iter().any(|(_, r)| r.as_ref().is_ok_and(|o| o.best.is_none())));
+    assert!(html.contains(&format!("<p class=\"error\">{}</p>", SolveError::NoFeasibleStrategy)));
+}
+
+#[test]
+fn profile_shows_pit_stops_as_jumps() {
+    let race_data = parse_input("examples/pit_stop_track.txt").unwrap();
+    let params = params(race_data.n - 1);
+    let cars = solve(&race_data, &params);
+    let path_info = cars[1].1.as_ref().unwrap().best.as_ref().unwrap();
+    let profile = visual::race_profile(path_info, &race_data.cars[1]);
+    // Start, one point per segment, and one after the pit stop
+    assert_eq!(profile.len(), 1 + path_info.segments.len() + path_info.pit_stops.len());
+    let pit = profile.iter().position(|p| p.pit_stop).unwrap();
+    assert_eq!(profile[pit].distance, profile[pit - 1].distance);
+    assert_eq!(profile[pit].fuel, race_data.cars[1].fuel_capacity);
+    assert_eq!(profile[pit].tyre_wear, Fixed::ZERO);
+    assert_eq!(profile.last().unwrap().time, path_info.total_time);
+    assert_eq!(profile.last().unwrap().distance, path_info.segments.iter().map(|s| s.distance).sum());
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::params;
+use f1_track::bidirectional::{find_optimal_path_bidirectional, solve_bidirectional};
+use f1_track::graph_rcspp::{find_optimal_path, optimality_gap, solve, Graph, RaceParams, Resolution, SolveError, SolveOptions, SolveStatus};
+use f1_track::input_parser::{parse_input, parse_str, CarConfig};
+use f1_track::units::Fixed;
+
+#[test]
+fn values_that_could_overflow_are_rejected() {
+    assert!(parse_str("2\n0\n1\n1\n0 1 1000000\n10 1\n").is_ok());
+    let long_edge = parse_str("3\n0\n1\n2\n0 1 5000000000000000\n1 2 5000000000000000\n10 1\n").unwrap_err();
+    assert!(long_edge.to_string().contains("above the limit of 1000000"), "{}", long_edge);
+    assert!(parse_str("2\n0\n1\n1\n0 1 9000000000000\n9000000000000 9000000000000\n").is_err());
+}
+
+#[test]
+fn overflowing_race_time_is_an_error() {
+    // The only way round pits, and the pit stop alone leaves no room in `Fixed`
+    let race_data = parse_str("3\n1\n1\n2\n0 1 10\n1 2 10\n1\n15 1\n").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let params = RaceParams { pit_stop_penalty: Fixed::from_raw(i64::MAX - 5000), ..params(2) };
+    let result = solve(&race_data, &graph, &race_data.cars[0], &params, &SolveOptions::default());
+    assert_eq!(result.unwrap_err(), SolveError::TimeOverflow);
+    let result = solve_bidirectional(&race_data, &graph, &race_data.cars[0], &params, &SolveOptions::default());
+    assert_eq!(result.unwrap_err(), SolveError::TimeOverflow);
+
+    // A car that need not pit is unaffected
+    let car = CarConfig { fuel_capacity: Fixed::from_int(20), tyre_cost: Fixed::from_int(1) };
+    let outcome = solve(&race_data, &graph, &car, &params, &SolveOptions::default()).unwrap();
+    assert_eq!(outcome.best.unwrap().total_time, Fixed::from_int(20));
+    let outcome = solve_bidirectional(&race_data, &graph, &car, &params, &SolveOptions::default()).unwrap();
+    assert_eq!(outcome.best.unwrap().total_time, Fixed::from_int(20));
+}
+
+#[test]
+fn bucketed_gap_separates_rounding_from_strategy() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let car = &race_data.cars[0];
+    let exact = find_optimal_path(&race_data, &graph, car, &params(7), &SolveOptions::default()).best.unwrap();
+    for buckets in [5, 8, 20, 100] {
+        let bucketed = RaceParams { resolution: Resolution::buckets(car, buckets), ..params(7) };
+        let outcome = find_optimal_path(&race_data, &graph, car, &bucketed, &SolveOptions::default());
+        let Some(strategy) = outcome.best else { continue };
+        // Rounded-up tyre wear only ever costs time, so the bucketed race is never faster
+        assert!(strategy.total_time >= exact.total_time);
+        let gap = optimality_gap(&race_data, &graph, car, &bucketed, &SolveOptions::default(), Some(&strategy));
+        assert_eq!(gap.exact_status, SolveStatus::Optimal);
+        assert_eq!(gap.exact_time, Some(exact.total_time));
+        let replayed = gap.replayed_time.unwrap();
+        assert!(exact.total_time <= replayed && replayed <= strategy.total_time);
+        assert_eq!(gap.absolute().unwrap() + gap.rounding().unwrap(), strategy.total_time - exact.total_time);
+    }
+
+    // Five buckets score car 1's usual route at 1080.4, all of it rounding
+    let bucketed = RaceParams { resolution: Resolution::buckets(car, 5), ..params(7) };
+    let strategy = find_optimal_path(&race_data, &graph, car, &bucketed, &SolveOptions::default()).best.unwrap();
+    let gap = optimality_gap(&race_data, &graph, car, &bucketed, &SolveOptions::default(), Some(&strategy));
+    assert_eq!(gap.absolute(), Some(Fixed::ZERO));
+    assert_eq!(gap.rounding(), Some(strategy.total_time - exact.total_time));
+}
+
+#[test]
+fn gap_solve_keeps_the_callers_budgets() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let car = &race_data.cars[0];
+    let bucketed = RaceParams { resolution: Resolution::buckets(car, 5), ..params(7) };
+    let options = SolveOptions { max_labels: Some(2), ..SolveOptions::default() };
+    let gap = optimality_gap(&race_data, &graph, car, &bucketed, &options, None);
+    assert_ne!(gap.exact_status, SolveStatus::Optimal);
+}
+
+#[test]
+fn tiny_label_budget_keeps_incumbent_and_bound() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    for car in &race_data.cars {
+        let exact = find_optimal_path(&race_data, &graph, car, &params(7), &SolveOptions::default()).best.unwrap();
+        let mut found_incumbent = false;
+        for max_labels in 1..200 {
+            let options = SolveOptions { max_labels: Some(max_labels), ..SolveOptions::default() };
+            let outcome = find_optimal_path(&race_data, &graph, car, &params(7), &options);
+            if outcome.status == SolveStatus::Optimal {
+                break;
+            }
+            // Soft limit: the expansion that crosses the budget may push a few more labels
+            assert!(outcome.stats.labels_created <= max_labels + 2 * graph.adj.values().map(Vec::len).max().unwrap());
+            assert!(outcome.lower_bound.unwrap() <= exact.total_time);
+            match outcome.status {
+                SolveStatus::BudgetExceeded => assert!(outcome.best.is_none()),
+                SolveStatus::Feasible => {
+                    // Debug builds have already replayed the incumbent through the verifier
+                    let incumbent = outcome.best.unwrap();
+                    assert!(incumbent.total_time >= exact.total_time);
+                    found_incumbent = true;
+                }
+                status => panic!("unexpected status {}", status),
+            }
+        }
+        let options = SolveOptions { max_labels: Some(1), ..SolveOptions::default() };
+        let result = solve(&race_data, &graph, car, &params(7), &options).unwrap().into_result();
+        assert_eq!(result.unwrap_err(), SolveError::BudgetExceeded);
+        assert!(found_incumbent, "some budget stops the search after a strategy is found");
+    }
+}
+
+#[test]
+fn bidirectional_search_keeps_the_callers_budgets() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let reversed = graph.reversed();
+    let max_degree = graph.adj.values().chain(reversed.adj.values()).map(Vec::len).max().unwrap();
+    for car in &race_data.cars {
+        let exact = find_optimal_path(&race_data, &graph, car, &params(7), &SolveOptions::default()).best.unwrap();
+        for max_labels in 1..200 {
+            let options = SolveOptions { max_labels: Some(max_labels), ..SolveOptions::default() };
+            let outcome = find_optimal_path_bidirectional(&race_data, &graph, car, &params(7), &options);
+            if outcome.status == SolveStatus::Optimal {
+                assert_eq!(outcome.best.unwrap().total_time, exact.total_time);
+                break;
+            }
+            // Each half seeds one label before its first check, then an expansion may push two per edge
+            assert!(outcome.stats.labels_created <= max_labels + 1 + 2 * max_degree);
+            assert!(outcome.lower_bound.unwrap() <= exact.total_time);
+            match outcome.status {
+                SolveStatus::BudgetExceeded => assert!(outcome.best.is_none()),
+                SolveStatus::Feasible => assert!(outcome.best.unwrap().total_time >= exact.total_time),
+                status => panic!("unexpected status {}", status),
+            }
+        }
+        let options = SolveOptions { max_labels: Some(1), ..SolveOptions::default() };
+        let result = solve_bidirectional(&race_data, &graph, car, &params(7), &options).unwrap().into_result();
+        assert_eq!(result.unwrap_err(), SolveError::BudgetExceeded);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+mod common;
+
+use f1_track::graph_rcspp::{find_optimal_path, Graph, SolveOptions};
+use f1_track::input_parser::{parse_input, RaceData};
+use f1_track::track_map::{main_line, track_map, MapOptions};
+
+fn plain_map(race_data: &RaceData, end: usize, strategy: Option<&f1_track::graph_rcspp::PathInfo>, options: &MapOptions) -> Vec<Vec<String>> {
+    colored::control::set_override(false);
+    track_map(race_data, &race_data.adjacency(), 0, end, strategy, options)
+}
+
+#[test]
+fn main_line_follows_the_circuit_past_shortcuts_and_pit_lanes() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let line = main_line(&race_data.adjacency(), 0, &HashSet::from([6]));
+    assert_eq!(line, vec![0, 1, 2, 3, 4, 5, 7]);
+}
+
+#[test]
+fn map_draws_shortcuts_above_and_back_edges_and_pit_lanes_below() {
+    let race_data = parse_input("examples/circuit_track.txt").unwrap();
+    let pages = plain_map(&race_data, 7, None, &MapOptions { width: 200, collapse: None });
+    assert_eq!(pages.len(), 1);
+    let map = &pages[0];
+    let main = map.iter().position(|l| l.contains("[0 S]")).unwrap();
+    assert!(map[main].contains("[0 S]─40─▶[1]"), "{:?}", map);
+    assert!(map[main].contains("[7 F]"));
+    // The shortcut 2 -> 4 above the line, the pit lane through node 6 and the lap back to 0 below it
+    assert!(map[..main].iter().any(|l| l.contains(" 55 ")), "{:?}", map);
+    assert!(map[main + 1..].iter().any(|l| l.contains(" 22.4 [P6] 20.6 ")), "{:?}", map);
+    assert!(map[main + 1..].iter().any(|l| l.contains(" 20.6 ") && l.contains('╰')), "{:?}", map);
+    assert!(!map.concat().contains('━'));
+}
+
+#[test]
+fn strategy_overlay_marks_driven_edges_and_pit_stops() {
+    let (race_data, params) = common::circuit();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let path_info = find_optimal_path(&race_data, &graph, &race_data.cars[1], &params, &SolveOptions::default()).into_result().unwrap();
+    let pages = plain_map(&race_data, 7, Some(&path_info), &MapOptions { width: 200, collapse: None });
+    let map = pages[0].join("\n");
+    assert!(map.contains("[0 S]━40━▶[1]━31.6━▶[2]─31.6─▶[3]"), "{}", map);
+    assert!(map.contains("━ 55 ━"), "{}", map);
+    assert!(map.contains("[P6 *]"), "{}", map);
+}
+
+#[test]
+fn large_tracks_are_paged_and_folded() {
+    let n = 80;
+    let text = format!(
+        "{}\n0\n0\n{}\n{}",
+        n,
+        n,
+        (0..n).map(|i| format!("{} {} 10\n", i, (i + 1) % n)).collect::<String>()
+    );
+    let race_data = f1_track::input_parser::parse_str(&text).unwrap();
+    let unfolded = plain_map(&race_data, n - 1, None, &MapOptions { width: 80, collapse: Some(false) });
+    assert!(unfolded.len() > 1);
+    for page in &unfolded {
+        assert!(page.iter().all(|l| l.chars().count() <= 80), "{:?}", page);
+    }
+    assert!(unfolded.iter().flatten().any(|l| l.contains("[42]")));
+
+    let folded = plain_map(&race_data, n - 1, None, &MapOptions { width: 80, collapse: None });
+    assert_eq!(folded.len(), 1, "{:?}", folded);
+    assert!(folded[0].iter().any(|l| l.contains("[0 S]┄78 nodes, 790┄▶[79 F]")), "{:?}", folded);
+}
+
+#[test]
+fn edges_to_a_lane_pit_arc_to_its_box() {
+    // Node 2 is a pit lane beside 1 -> 3, with a second way in straight from the start
+    let race_data = f1_track::input_parser::parse_str("5\n1\n1\n7\n0 1 10\n1 3 10\n3 4 10\n4 0 10\n1 2 5\n2 3 5\n0 2 7\n2\n100 1\n").unwrap();
+    let map = &plain_map(&race_data, 4, None, &MapOptions { width: 200, collapse: None })[0];
+    let lane = map.iter().position(|l| l.contains(" 5 [P2] 5 ")).unwrap();
+    let box_at = map[lane].chars().position(|c| c == '[').unwrap();
+    let arrow = map[lane + 1].chars().position(|c| c == '▲').unwrap();
+    assert!((box_at..box_at + 4).contains(&arrow), "{:?}", map);
+    assert!(map[lane + 2..].iter().any(|l| l.contains(" 7 ")), "{:?}", map);
+
+    // The fastest way drives 0 -> P2 -> 3 -> 4: the arc in and only the lane's half out of the pit
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let params = common::params(4);
+    let path_info = find_optimal_path(&race_data, &graph, &race_data.cars[0], &params, &SolveOptions::default()).into_result().unwrap();
+    assert_eq!(path_info.node_sequence, vec![0, 2, 3, 4]);
+    let map = &plain_map(&race_data, 4, Some(&path_info), &MapOptions { width: 200, collapse: None })[0];
+    assert!(map.iter().any(|l| l.contains("━ 7 ━")), "{:?}", map);
+    let lane = map.iter().find(|l| l.contains("[P2]")).unwrap();
+    let (into, out) = lane.split_at(lane.find("[P2]").unwrap());
+    assert!(!into.contains('━') && out.contains('━'), "{:?}", map);
+    assert!(map.iter().any(|l| l.contains("[3]━10━▶[4 F]")), "{:?}", map);
+}
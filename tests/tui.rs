@@ -0,0 +1,106 @@
+mod common;
+
+use f1_track::graph_rcspp::{RaceParams, SolveOptions};
+use f1_track::tui::{self, App, Param};
+use f1_track::units::Fixed;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::Terminal;
+
+fn circuit_app(laps: u32) -> App {
+    let (race_data, params) = common::circuit();
+    let params = RaceParams { total_laps: laps, ..params };
+    App::new("examples/circuit_track.txt".to_string(), race_data, vec![0, 1], params, None, SolveOptions::default())
+}
+
+// Every row of the rendered screen
+fn screen(app: &App) -> Vec<String> {
+    colored::control::set_override(false);
+    let mut terminal = Terminal::new(TestBackend::new(140, 44)).unwrap();
+    terminal.draw(|frame| tui::draw(frame, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let width = buffer.area.width as usize;
+    buffer.content().chunks(width).map(|row| row.iter().map(|cell| cell.symbol()).collect()).collect()
+}
+
+#[test]
+fn steps_lap_by_lap_and_segment_by_segment() {
+    let mut app = circuit_app(2);
+    let segments = app.strategy().unwrap().segments.clone();
+    assert_eq!(app.lap(), 0);
+    app.handle_key(KeyCode::Right);
+    assert_eq!(app.lap(), 1);
+    assert_eq!(segments[app.position - 1].lap, 0);
+    app.handle_key(KeyCode::Right);
+    assert_eq!(app.position, segments.len());
+    app.handle_key(KeyCode::Right);
+    assert_eq!(app.position, segments.len());
+    app.handle_key(KeyCode::Left);
+    assert_eq!(app.lap(), 1);
+    app.handle_key(KeyCode::Char(','));
+    assert_eq!(app.lap(), 0);
+    app.handle_key(KeyCode::Home);
+    assert_eq!(app.position, 0);
+
+    // Playback advances one segment per tick and stops at the finish
+    app.handle_key(KeyCode::Char(' '));
+    assert!(app.playing);
+    for _ in 0..segments.len() {
+        app.tick();
+    }
+    assert_eq!(app.position, segments.len());
+    assert!(!app.playing);
+    assert!(!app.handle_key(KeyCode::Char('q')));
+}
+
+#[test]
+fn changing_a_parameter_re_solves_every_car() {
+    let mut app = circuit_app(1);
+    let times = |app: &App| -> Vec<Option<Fixed>> {
+        app.results.iter().map(|r| r.as_ref().ok().and_then(|o| o.best.as_ref()).map(|p| p.total_time)).collect()
+    };
+    let before = times(&app);
+    app.handle_key(KeyCode::Down);
+    app.handle_key(KeyCode::Right);
+    assert!(app.position > 0);
+    app.handle_key(KeyCode::Char('+'));
+    assert_eq!(app.params.total_laps, 2);
+    assert_eq!(app.position, 0);
+    // Car 1 needs longer over two laps; car 2 cannot go that far on its tank
+    let after = times(&app);
+    assert!(after[0] > before[0], "{:?} {:?}", before, after);
+    assert_eq!(after[1], None);
+
+    app.handle_key(KeyCode::Char('-'));
+    app.handle_key(KeyCode::Char('-'));
+    assert_eq!(app.params.total_laps, 1);
+
+    app.handle_key(KeyCode::Tab);
+    assert_eq!(app.param, Param::PitPenalty);
+    app.handle_key(KeyCode::Char('+'));
+    assert_eq!(app.params.pit_stop_penalty, Fixed::from_int(65));
+    app.handle_key(KeyCode::Tab);
+    app.handle_key(KeyCode::Char('-'));
+    assert_eq!(app.params.tyre_wear_distance, Fixed::from_int(90));
+}
+
+#[test]
+fn draws_cars_track_gauges_and_the_current_lap() {
+    let mut app = circuit_app(1);
+    app.handle_key(KeyCode::Down);
+    for _ in 0..5 {
+        app.handle_key(KeyCode::Char('.'));
+    }
+    let screen = screen(&app);
+    let text = screen.join("\n");
+    assert!(text.contains("Laps 1"), "{}", text);
+    assert!(text.contains("Car 1"), "{}", text);
+    assert!(screen.iter().any(|l| l.contains("▶ Car 2")), "{}", text);
+    assert!(text.contains("[0 S]") && text.contains("[7 F]"), "{}", text);
+    assert!(text.contains("[P6 *]"), "{}", text);
+    assert!(text.contains(" Fuel "), "{}", text);
+    assert!(text.contains(" Tyre wear "), "{}", text);
+    // Five segments in, car 2 is at node 6 about to pit, with the pit stop on the lap table
+    assert!(text.contains("lap 1, at node 6"), "{}", text);
+    assert!(text.contains("PIT"), "{}", text);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use f1_track::graph_rcspp::{find_optimal_path, Graph, PathInfo, SolveOptions};
+use f1_track::input_parser::RaceData;
+use f1_track::units::Fixed;
+use f1_track::verify::{verify_strategy, Discrepancy};
+
+// Car 2's strategy on the circuit, which pits at node 6 to make the finish
+fn pitting_strategy() -> (RaceData, Graph, PathInfo) {
+    let (race_data, params) = common::circuit();
+    let graph = Graph::new(race_data.n, &race_data.edges);
+    let best = find_optimal_path(&race_data, &graph, &race_data.cars[1], &params, &SolveOptions::default()).best.unwrap();
+    assert_eq!(best.pit_stops, vec![(0, 6)]);
+    (race_data, graph, best)
+}
+
+fn discrepancies(race_data: &RaceData, graph: &Graph, path_info: &PathInfo) -> Vec<Discrepancy> {
+    verify_strategy(race_data, graph, &race_data.cars[1], &common::params(7), path_info).unwrap_err()
+}
+
+#[test]
+fn solver_strategy_passes() {
+    let (race_data, graph, best) = pitting_strategy();
+    assert_eq!(verify_strategy(&race_data, &graph, &race_data.cars[1], &common::params(7), &best), Ok(()));
+}
+
+#[test]
+fn wrong_total_is_rejected() {
+    let (race_data, graph, mut best) = pitting_strategy();
+    let recomputed = best.total_time;
+    best.total_time += Fixed::EPSILON;
+    assert_eq!(discrepancies(&race_data, &graph, &best), vec![Discrepancy::TimeMismatch { reported: best.total_time, recomputed }]);
+}
+
+#[test]
+fn missing_edge_is_rejected() {
+    let (race_data, graph, mut best) = pitting_strategy();
+    // There is no edge 1 -> 4, only 1 -> 2 -> 4
+    best.node_sequence.remove(2);
+    let found = discrepancies(&race_data, &graph, &best);
+    assert!(found.contains(&Discrepancy::MissingEdge { index: 1, from: 1, to: 4 }), "{:?}", found);
+}
+
+#[test]
+fn running_out_of_fuel_is_rejected() {
+    let (race_data, graph, mut best) = pitting_strategy();
+    // Without the pit stop, 5.7 fuel is left for the 20.6 to the finish
+    best.pit_stops.clear();
+    let found = discrepancies(&race_data, &graph, &best);
+    assert!(
+        found.iter().any(|d| matches!(d, Discrepancy::NegativeFuel { from: 6, to: 7, .. })),
+        "{:?}",
+        found
+    );
+}
+
+#[test]
+fn pit_stop_at_a_non_pit_node_is_rejected() {
+    let (race_data, graph, mut best) = pitting_strategy();
+    best.pit_stops = vec![(0, 5)];
+    let found = discrepancies(&race_data, &graph, &best);
+    assert!(found.contains(&Discrepancy::PitAtNonPitNode { lap: 0, node: 5 }), "{:?}", found);
+}